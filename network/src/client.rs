@@ -1,44 +1,66 @@
-pub mod request;
-use std::env;
-use std::fs::File;
-
 use coprocessor_sdk::sdk::SDK;
-use gateway::gateway_client::GatewayClient;
-use request::prepare_request;
-use std::io::BufReader;
+use tonic::transport::Channel;
+
+use crate::{
+    config::GatewayConfig,
+    error::{check_err, GatewayError},
+    gateway::{
+        gateway_client::GatewayClient, CircuitDigestRequest, CircuitDigestResponse,
+        SendBatchQueriesAsyncResponse, SendBatchQueriesRequest,
+    },
+    request::prepare_sdk_request,
+};
 
-pub mod gateway {
-    tonic::include_proto!("brevis");
+/// Async client of the Brevis gateway, for requesting the proofs of the inputs saved by the SDK.
+pub struct CoprocessorClient {
+    inner: GatewayClient<Channel>,
+    api_key: String,
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let input_json = env::var("REQUEST_DATA_FILE").expect("REQUST_DATA_FILE not set");
-    // Open the file
-    let file = File::open(input_json)?;
-    let reader = BufReader::new(file);
-    let input_data: SDK = serde_json::from_reader(reader).unwrap();
+impl CoprocessorClient {
+    pub async fn connect(config: &GatewayConfig) -> Result<Self, GatewayError> {
+        Ok(CoprocessorClient {
+            inner: config.connect().await?,
+            api_key: config.api_key.clone(),
+        })
+    }
 
-    let limits = vec![
-        input_data.max_receipt_size,
-        input_data.max_storage_size,
-        input_data.max_tx_size,
-    ];
-    let request = prepare_request(
-        input_data.chain_id,
-        input_data.receipts,
-        input_data.storage_slots,
-        input_data.transactions,
-        limits
-    );
-    println!("request: {:?}", request);
-    let mut client: GatewayClient<tonic::transport::Channel> =
-        GatewayClient::connect("https://appsdkv3.brevis.network:443").await?;
+    /// Builds the request proving the inputs of `sdk`, without sending it. Fails for inputs
+    /// the gateway can't prove yet, see `prepare_sdk_request`.
+    pub fn prepare_request(&self, sdk: &SDK) -> Result<SendBatchQueriesRequest, GatewayError> {
+        prepare_sdk_request(sdk, &self.api_key)
+    }
 
-    let request = tonic::Request::new(request);
-    let response = client.send_batch_queries_async(request).await?;
+    /// Requests proving the inputs of `sdk`.
+    pub async fn submit(
+        &mut self,
+        sdk: &SDK,
+    ) -> Result<SendBatchQueriesAsyncResponse, GatewayError> {
+        let request = self.prepare_request(sdk)?;
+        self.send(request).await
+    }
 
-    println!("RESPONSE={:?}", response);
+    /// Sends a prepared request, failing if the gateway reports an error in the response.
+    pub async fn send(
+        &mut self,
+        request: SendBatchQueriesRequest,
+    ) -> Result<SendBatchQueriesAsyncResponse, GatewayError> {
+        let response = self
+            .inner
+            .send_batch_queries_async(tonic::Request::new(request))
+            .await?
+            .into_inner();
+        check_err(response.err.as_ref())?;
+        Ok(response)
+    }
 
-    Ok(())
+    pub async fn get_circuit_digest(&mut self) -> Result<CircuitDigestResponse, GatewayError> {
+        let response = self
+            .inner
+            .get_circuit_digest(tonic::Request::new(CircuitDigestRequest {}))
+            .await?
+            .into_inner();
+        check_err(response.err.as_ref())?;
+        Ok(response)
+    }
 }
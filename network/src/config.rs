@@ -0,0 +1,208 @@
+use std::{env, fs, path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint};
+
+use crate::gateway::gateway_client::GatewayClient;
+
+pub const DEFAULT_ENDPOINT: &str = "https://appsdkv3.brevis.network:443";
+pub const DEFAULT_API_KEY: &str = "TESTVM";
+pub const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+pub const ENV_ENDPOINT: &str = "BREVIS_GATEWAY_ENDPOINT";
+pub const ENV_API_KEY: &str = "BREVIS_GATEWAY_API_KEY";
+pub const ENV_TIMEOUT_SECS: &str = "BREVIS_GATEWAY_TIMEOUT_SECS";
+pub const ENV_CA_CERT: &str = "BREVIS_GATEWAY_CA_CERT";
+pub const ENV_INSECURE: &str = "BREVIS_GATEWAY_INSECURE";
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("failed to read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid gateway config file: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("invalid value {value:?} for {name}")]
+    InvalidVar { name: &'static str, value: String },
+    #[error("plain-text endpoint {0} requires the insecure option")]
+    InsecureEndpoint(String),
+    #[error("unsupported endpoint scheme in {0}")]
+    UnsupportedScheme(String),
+    #[error(transparent)]
+    Transport(#[from] tonic::transport::Error),
+}
+
+/// Connection settings of the Brevis gateway.
+///
+/// Settings are layered: defaults, then an optional TOML file, then `BREVIS_GATEWAY_*`
+/// environment variables, then command line flags.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GatewayConfig {
+    pub endpoint: String,
+    pub api_key: String,
+    pub timeout_secs: u64,
+    /// PEM file of an extra CA to trust, e.g. for a self-hosted gateway.
+    pub ca_cert: Option<PathBuf>,
+    /// Allows a plain-text `http://` endpoint. Only meant for local gateways.
+    pub insecure: bool,
+}
+
+impl Default for GatewayConfig {
+    fn default() -> Self {
+        GatewayConfig {
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            api_key: DEFAULT_API_KEY.to_string(),
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            ca_cert: None,
+            insecure: false,
+        }
+    }
+}
+
+impl GatewayConfig {
+    /// Defaults overridden by the `BREVIS_GATEWAY_*` environment variables.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::default().with_env()
+    }
+
+    pub fn from_toml_str(toml_str: &str) -> Result<Self, ConfigError> {
+        Ok(toml::from_str(toml_str)?)
+    }
+
+    pub fn from_toml_file(path: impl Into<PathBuf>) -> Result<Self, ConfigError> {
+        let path = path.into();
+        let toml_str = fs::read_to_string(&path).map_err(|source| ConfigError::Io { path, source })?;
+        Self::from_toml_str(&toml_str)
+    }
+
+    /// Overrides the settings with the `BREVIS_GATEWAY_*` environment variables that are set.
+    pub fn with_env(self) -> Result<Self, ConfigError> {
+        self.with_vars(|name| env::var(name).ok())
+    }
+
+    fn with_vars(mut self, var: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigError> {
+        if let Some(endpoint) = var(ENV_ENDPOINT) {
+            self.endpoint = endpoint;
+        }
+        if let Some(api_key) = var(ENV_API_KEY) {
+            self.api_key = api_key;
+        }
+        if let Some(value) = var(ENV_TIMEOUT_SECS) {
+            self.timeout_secs = value.parse().map_err(|_| ConfigError::InvalidVar {
+                name: ENV_TIMEOUT_SECS,
+                value,
+            })?;
+        }
+        if let Some(ca_cert) = var(ENV_CA_CERT) {
+            self.ca_cert = Some(ca_cert.into());
+        }
+        if let Some(value) = var(ENV_INSECURE) {
+            self.insecure = match value.to_ascii_lowercase().as_str() {
+                "1" | "true" | "yes" => true,
+                "0" | "false" | "no" | "" => false,
+                _ => {
+                    return Err(ConfigError::InvalidVar {
+                        name: ENV_INSECURE,
+                        value,
+                    })
+                }
+            };
+        }
+        Ok(self)
+    }
+
+    /// Builds the tonic endpoint, enabling TLS for `https://` endpoints.
+    pub fn endpoint(&self) -> Result<Endpoint, ConfigError> {
+        let timeout = Duration::from_secs(self.timeout_secs);
+        let endpoint = Endpoint::from_shared(self.endpoint.clone())?
+            .timeout(timeout)
+            .connect_timeout(timeout);
+
+        match endpoint.uri().scheme_str() {
+            Some("https") => {
+                let mut tls = ClientTlsConfig::new().with_enabled_roots();
+                if let Some(ca_cert) = &self.ca_cert {
+                    let pem = fs::read(ca_cert).map_err(|source| ConfigError::Io {
+                        path: ca_cert.clone(),
+                        source,
+                    })?;
+                    tls = tls.ca_certificate(Certificate::from_pem(pem));
+                }
+                Ok(endpoint.tls_config(tls)?)
+            }
+            Some("http") if self.insecure => Ok(endpoint),
+            Some("http") => Err(ConfigError::InsecureEndpoint(self.endpoint.clone())),
+            _ => Err(ConfigError::UnsupportedScheme(self.endpoint.clone())),
+        }
+    }
+
+    pub async fn connect(&self) -> Result<GatewayClient<Channel>, ConfigError> {
+        let channel = self.endpoint()?.connect().await?;
+        Ok(GatewayClient::new(channel))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{ConfigError, GatewayConfig, DEFAULT_ENDPOINT};
+
+    #[test]
+    fn test_toml_config() {
+        let config = GatewayConfig::from_toml_str(
+            r#"
+            endpoint = "https://gateway.staging.example:443"
+            api_key = "staging-key"
+            ca_cert = "/etc/brevis/ca.pem"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.endpoint, "https://gateway.staging.example:443");
+        assert_eq!(config.api_key, "staging-key");
+        assert_eq!(config.timeout_secs, GatewayConfig::default().timeout_secs);
+        assert_eq!(config.ca_cert, Some("/etc/brevis/ca.pem".into()));
+        assert!(!config.insecure);
+
+        assert!(GatewayConfig::from_toml_str("endpont = \"typo\"").is_err());
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let vars = HashMap::from([
+            ("BREVIS_GATEWAY_API_KEY", "env-key"),
+            ("BREVIS_GATEWAY_TIMEOUT_SECS", "5"),
+            ("BREVIS_GATEWAY_INSECURE", "true"),
+        ]);
+        let config = GatewayConfig::default()
+            .with_vars(|name| vars.get(name).map(|v| v.to_string()))
+            .unwrap();
+        assert_eq!(config.endpoint, DEFAULT_ENDPOINT);
+        assert_eq!(config.api_key, "env-key");
+        assert_eq!(config.timeout_secs, 5);
+        assert!(config.insecure);
+
+        let err = GatewayConfig::default()
+            .with_vars(|name| (name == "BREVIS_GATEWAY_TIMEOUT_SECS").then(|| "soon".to_string()))
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::InvalidVar { .. }));
+    }
+
+    #[test]
+    fn test_plain_text_endpoint_requires_insecure() {
+        let mut config = GatewayConfig {
+            endpoint: "http://127.0.0.1:50051".to_string(),
+            ..GatewayConfig::default()
+        };
+        assert!(matches!(
+            config.endpoint(),
+            Err(ConfigError::InsecureEndpoint(_))
+        ));
+        config.insecure = true;
+        assert!(config.endpoint().is_ok());
+    }
+}
@@ -0,0 +1,117 @@
+use thiserror::Error;
+
+use crate::{
+    config::ConfigError,
+    gateway::{ErrCode, ErrMsg},
+};
+
+#[derive(Debug, Error)]
+pub enum GatewayError {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error("gateway request failed: {0}")]
+    Status(Box<tonic::Status>),
+    #[error("slot value proof generation failed: {0}")]
+    SlotValueProofGenerationFailed(String),
+    #[error("transaction proof generation failed: {0}")]
+    TransactionProofGenerationFailed(String),
+    #[error("gateway error: {0}")]
+    Undefined(String),
+    #[error("unknown gateway error code {code}: {msg}")]
+    Unknown { code: i32, msg: String },
+    #[error("the gateway can't prove {0} yet")]
+    UnsupportedInput(&'static str),
+}
+
+impl GatewayError {
+    /// Maps the `err` of a gateway response. The gateway always sets it, an undefined code
+    /// with an empty message means success.
+    pub fn from_err_msg(err: &ErrMsg) -> Option<Self> {
+        let msg = err.msg.clone();
+        match ErrCode::try_from(err.code) {
+            Ok(ErrCode::ErrorCodeUndefined) if msg.is_empty() => None,
+            Ok(ErrCode::ErrorCodeUndefined) => Some(GatewayError::Undefined(msg)),
+            Ok(ErrCode::ErrorCodeSlotValueProofGenerationFailed) => {
+                Some(GatewayError::SlotValueProofGenerationFailed(msg))
+            }
+            Ok(ErrCode::ErrorCodeTransactionProofGenerationFailed) => {
+                Some(GatewayError::TransactionProofGenerationFailed(msg))
+            }
+            Err(_) => Some(GatewayError::Unknown {
+                code: err.code,
+                msg,
+            }),
+        }
+    }
+
+    /// The gateway error code, `None` if the request did not reach the gateway.
+    pub fn code(&self) -> Option<ErrCode> {
+        match self {
+            GatewayError::Config(_)
+            | GatewayError::Status(_)
+            | GatewayError::Unknown { .. }
+            | GatewayError::UnsupportedInput(_) => None,
+            GatewayError::SlotValueProofGenerationFailed(_) => {
+                Some(ErrCode::ErrorCodeSlotValueProofGenerationFailed)
+            }
+            GatewayError::TransactionProofGenerationFailed(_) => {
+                Some(ErrCode::ErrorCodeTransactionProofGenerationFailed)
+            }
+            GatewayError::Undefined(_) => Some(ErrCode::ErrorCodeUndefined),
+        }
+    }
+}
+
+impl From<tonic::Status> for GatewayError {
+    fn from(status: tonic::Status) -> Self {
+        GatewayError::Status(Box::new(status))
+    }
+}
+
+pub(crate) fn check_err(err: Option<&ErrMsg>) -> Result<(), GatewayError> {
+    match err.and_then(GatewayError::from_err_msg) {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_err, GatewayError};
+    use crate::gateway::{ErrCode, ErrMsg};
+
+    fn err_msg(code: i32, msg: &str) -> ErrMsg {
+        ErrMsg {
+            code,
+            msg: msg.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_from_err_msg() {
+        assert!(check_err(None).is_ok());
+        assert!(check_err(Some(&ErrMsg::default())).is_ok());
+
+        let slot_err = GatewayError::from_err_msg(&err_msg(1, "slot not found")).unwrap();
+        assert!(matches!(
+            &slot_err,
+            GatewayError::SlotValueProofGenerationFailed(msg) if msg == "slot not found"
+        ));
+        assert_eq!(
+            slot_err.code(),
+            Some(ErrCode::ErrorCodeSlotValueProofGenerationFailed)
+        );
+        assert!(matches!(
+            GatewayError::from_err_msg(&err_msg(2, "tx not found")),
+            Some(GatewayError::TransactionProofGenerationFailed(_))
+        ));
+        assert!(matches!(
+            GatewayError::from_err_msg(&err_msg(0, "bad api key")),
+            Some(GatewayError::Undefined(_))
+        ));
+        assert!(matches!(
+            GatewayError::from_err_msg(&err_msg(9, "")),
+            Some(GatewayError::Unknown { code: 9, .. })
+        ));
+    }
+}
@@ -0,0 +1,14 @@
+pub mod client;
+pub mod config;
+pub mod error;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod request;
+
+pub use client::CoprocessorClient;
+pub use config::GatewayConfig;
+pub use error::GatewayError;
+
+pub mod gateway {
+    tonic::include_proto!("brevis");
+}
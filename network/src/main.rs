@@ -0,0 +1,196 @@
+use std::error::Error;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand};
+use coprocessor_sdk::{data_types::hash_out::HashBytes, inputs_file::InputsFile, sdk::SDK};
+use serde_json::json;
+use vm_coprocessor_server::{
+    config::{ConfigError, GatewayConfig},
+    request::prepare_sdk_request,
+    CoprocessorClient, GatewayError,
+};
+
+#[derive(Parser)]
+#[command(name = "brevis-request", version, about = "Brevis gateway client for Pico coprocessor inputs")]
+struct Cli {
+    /// Inputs saved by `SDK::save_inputs`
+    #[arg(long, global = true, env = "REQUEST_DATA_FILE")]
+    input: Option<PathBuf>,
+    /// Print machine-readable JSON
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(flatten)]
+    gateway: GatewayArgs,
+    /// Defaults to `submit`
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Send the saved inputs to the gateway
+    Submit,
+    /// Print the circuit digest of the gateway
+    Digest,
+    /// Print the saved inputs and check their header and input commitment
+    Inspect,
+    /// Print the request `submit` would send, without sending it
+    DryRun,
+}
+
+/// Gateway flags, applied on top of the config file and the `BREVIS_GATEWAY_*` variables.
+#[derive(Args)]
+struct GatewayArgs {
+    /// TOML file with the gateway settings
+    #[arg(long, global = true, env = "BREVIS_GATEWAY_CONFIG")]
+    config: Option<PathBuf>,
+    /// Gateway URL, e.g. https://appsdkv3.brevis.network:443
+    #[arg(long, global = true)]
+    endpoint: Option<String>,
+    #[arg(long, global = true)]
+    api_key: Option<String>,
+    /// Request and connect timeout in seconds
+    #[arg(long, global = true)]
+    timeout_secs: Option<u64>,
+    /// PEM file of an extra CA to trust
+    #[arg(long, global = true)]
+    ca_cert: Option<PathBuf>,
+    /// Allow a plain-text http:// endpoint, for local gateways only
+    #[arg(long, global = true)]
+    insecure: bool,
+}
+
+impl GatewayArgs {
+    fn load(self) -> Result<GatewayConfig, ConfigError> {
+        let config = match self.config {
+            Some(path) => GatewayConfig::from_toml_file(path)?,
+            None => GatewayConfig::default(),
+        };
+        let mut config = config.with_env()?;
+        if let Some(endpoint) = self.endpoint {
+            config.endpoint = endpoint;
+        }
+        if let Some(api_key) = self.api_key {
+            config.api_key = api_key;
+        }
+        if let Some(timeout_secs) = self.timeout_secs {
+            config.timeout_secs = timeout_secs;
+        }
+        if let Some(ca_cert) = self.ca_cert {
+            config.ca_cert = Some(ca_cert);
+        }
+        config.insecure |= self.insecure;
+        Ok(config)
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let json = cli.json;
+    match run(cli).await {
+        Ok(code) => code,
+        Err(e) => {
+            if json {
+                let code = e
+                    .downcast_ref::<GatewayError>()
+                    .and_then(GatewayError::code)
+                    .map(|code| code.as_str_name());
+                println!("{}", json!({ "error": e.to_string(), "code": code }));
+            } else {
+                eprintln!("error: {}", e);
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<ExitCode, Box<dyn Error>> {
+    let gateway_config = cli.gateway.load()?;
+    match cli.command.unwrap_or(Command::Submit) {
+        Command::Submit => {
+            let sdk = load_inputs(cli.input)?;
+            let mut client = CoprocessorClient::connect(&gateway_config).await?;
+            let response = client.submit(&sdk).await?;
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&response)?);
+            } else {
+                println!("batch id: {}", response.batch_id);
+                println!("request ids: {}", response.request_ids.join(", "));
+                println!("nonce: {}", response.nonce);
+            }
+        }
+        Command::Digest => {
+            let mut client = CoprocessorClient::connect(&gateway_config).await?;
+            let response = client.get_circuit_digest().await?;
+            if cli.json {
+                println!("{}", serde_json::to_string_pretty(&response)?);
+            } else {
+                println!("hashes limbs: {:?}", response.hashes_limbs);
+                println!("gnark vks:");
+                response.gnark_vks.iter().for_each(|vk| println!("  {}", vk));
+            }
+        }
+        Command::Inspect => {
+            let path = input_path(cli.input)?;
+            let file = InputsFile::read(&path)?;
+            let format_version = file.format_version;
+            let sdk = file.inputs.clone();
+            let recomputed = sdk.merkle_tree().root();
+            let verified = file.verify().map(|_| ()).map_err(|e| e.to_string());
+            if cli.json {
+                let report = json!({
+                    "format_version": format_version,
+                    "inputs": &sdk,
+                    "recomputed_input_commitments": recomputed,
+                    "commitment_matches": recomputed == sdk.input_commitments,
+                    "error": verified.as_ref().err(),
+                });
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!("{}", serde_json::to_string_pretty(&sdk)?);
+                println!("format version: {}", format_version);
+                println!("chain id: {}", sdk.chain_id);
+                println!(
+                    "receipts: {}/{}, storage slots: {}/{}, transactions: {}/{}, block headers: {}/{}, accounts: {}/{}",
+                    sdk.receipts.as_ref().map_or(0, Vec::len),
+                    sdk.max_receipt_size,
+                    sdk.storage_slots.as_ref().map_or(0, Vec::len),
+                    sdk.max_storage_size,
+                    sdk.transactions.as_ref().map_or(0, Vec::len),
+                    sdk.max_tx_size,
+                    sdk.block_headers.as_ref().map_or(0, Vec::len),
+                    sdk.max_block_header_size,
+                    sdk.accounts.as_ref().map_or(0, Vec::len),
+                    sdk.max_account_size,
+                );
+                println!("saved input commitment:      {}", commitment_hex(&sdk.input_commitments));
+                println!("recomputed input commitment: {}", commitment_hex(&recomputed));
+            }
+            if let Err(e) = verified {
+                eprintln!("error: {}", e);
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Command::DryRun => {
+            let request = prepare_sdk_request(&load_inputs(cli.input)?, &gateway_config.api_key)?;
+            println!("{}", serde_json::to_string_pretty(&request)?);
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn input_path(input: Option<PathBuf>) -> Result<PathBuf, Box<dyn Error>> {
+    Ok(input.ok_or("no inputs given, set --input or REQUEST_DATA_FILE")?)
+}
+
+/// Loads the inputs, verifying the header, the checksum and the input commitment.
+fn load_inputs(input: Option<PathBuf>) -> Result<SDK, Box<dyn Error>> {
+    let path = input_path(input)?;
+    SDK::load_inputs(&path).map_err(|e| format!("{}: {}", path.display(), e).into())
+}
+
+fn commitment_hex(commitment: &[u32; 8]) -> String {
+    format!("0x{}", hex::encode(commitment.to_be_bytes()))
+}
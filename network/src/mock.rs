@@ -0,0 +1,157 @@
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    transport::{server::TcpIncoming, Server},
+    Request, Response, Status,
+};
+
+use crate::{
+    config::GatewayConfig,
+    gateway::{
+        gateway_server::{Gateway, GatewayServer},
+        CircuitDigestRequest, CircuitDigestResponse, ErrCode, ErrMsg,
+        SendBatchQueriesAsyncResponse, SendBatchQueriesRequest,
+    },
+};
+
+pub const MOCK_BATCH_ID: &str = "mock-batch";
+pub const MOCK_REQUEST_ID: &str = "mock-request";
+
+/// In-process `Gateway` service for testing clients without the live Brevis gateway.
+/// It records every received request and answers with the configured responses.
+#[derive(Clone)]
+pub struct MockGateway {
+    state: Arc<Mutex<MockState>>,
+}
+
+struct MockState {
+    requests: Vec<SendBatchQueriesRequest>,
+    response: SendBatchQueriesAsyncResponse,
+    digest: CircuitDigestResponse,
+}
+
+impl Default for MockGateway {
+    fn default() -> Self {
+        MockGateway::new()
+    }
+}
+
+impl MockGateway {
+    pub fn new() -> Self {
+        let state = MockState {
+            requests: vec![],
+            response: SendBatchQueriesAsyncResponse {
+                err: Some(ErrMsg::default()),
+                batch_id: MOCK_BATCH_ID.to_string(),
+                request_ids: vec![MOCK_REQUEST_ID.to_string()],
+                nonce: 0,
+            },
+            digest: mock_digest(),
+        };
+        MockGateway {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    pub fn with_response(self, batch_id: &str, request_ids: Vec<String>, nonce: u64) -> Self {
+        {
+            let response = &mut self.state.lock().unwrap().response;
+            response.batch_id = batch_id.to_string();
+            response.request_ids = request_ids;
+            response.nonce = nonce;
+        }
+        self
+    }
+
+    /// Makes `SendBatchQueriesAsync` report `code` in the response `err`.
+    pub fn with_err(self, code: ErrCode, msg: &str) -> Self {
+        self.state.lock().unwrap().response.err = Some(ErrMsg {
+            code: code as i32,
+            msg: msg.to_string(),
+        });
+        self
+    }
+
+    pub fn with_digest(self, digest: CircuitDigestResponse) -> Self {
+        self.state.lock().unwrap().digest = digest;
+        self
+    }
+
+    /// The `SendBatchQueriesRequest`s received so far, in order.
+    pub fn requests(&self) -> Vec<SendBatchQueriesRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Serves the mock on a free localhost port until the returned server is dropped.
+    pub async fn serve(self) -> std::io::Result<MockServer> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let incoming =
+            TcpIncoming::from_listener(listener, true, None).map_err(std::io::Error::other)?;
+        let (shutdown, shutdown_rx) = oneshot::channel::<()>();
+        tokio::spawn(
+            Server::builder()
+                .add_service(GatewayServer::new(self))
+                .serve_with_incoming_shutdown(incoming, async {
+                    shutdown_rx.await.ok();
+                }),
+        );
+        Ok(MockServer {
+            addr,
+            _shutdown: shutdown,
+        })
+    }
+}
+
+/// The fixed digest served by default.
+pub fn mock_digest() -> CircuitDigestResponse {
+    CircuitDigestResponse {
+        err: Some(ErrMsg::default()),
+        hashes_limbs: vec![1, 2, 3, 4],
+        gnark_vks: vec!["mock-vk".to_string()],
+    }
+}
+
+#[tonic::async_trait]
+impl Gateway for MockGateway {
+    async fn send_batch_queries_async(
+        &self,
+        request: Request<SendBatchQueriesRequest>,
+    ) -> Result<Response<SendBatchQueriesAsyncResponse>, Status> {
+        let mut state = self.state.lock().unwrap();
+        state.requests.push(request.into_inner());
+        Ok(Response::new(state.response.clone()))
+    }
+
+    async fn get_circuit_digest(
+        &self,
+        _request: Request<CircuitDigestRequest>,
+    ) -> Result<Response<CircuitDigestResponse>, Status> {
+        Ok(Response::new(self.state.lock().unwrap().digest.clone()))
+    }
+}
+
+/// A running `MockGateway`, shut down on drop.
+pub struct MockServer {
+    addr: SocketAddr,
+    _shutdown: oneshot::Sender<()>,
+}
+
+impl MockServer {
+    pub fn endpoint(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// A plain-text config pointing to the mock.
+    pub fn config(&self) -> GatewayConfig {
+        GatewayConfig {
+            endpoint: self.endpoint(),
+            insecure: true,
+            ..GatewayConfig::default()
+        }
+    }
+}
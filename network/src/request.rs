@@ -1,8 +1,15 @@
 use coprocessor_sdk::{
-    input_types::{receipt::ReceiptData, storage::StorageSlotData, transaction::TransactionData},
+    commitment::CommitmentVersion,
+    input_types::{
+        receipt::ReceiptData,
+        storage::{AccountStorageData, StorageSlotData},
+        transaction::TransactionData,
+    },
+    sdk::SDK,
     Hex,
 };
 
+use crate::error::GatewayError;
 use crate::gateway::{
     LogExtractInfo, Query, ReceiptInfo, SendBatchQueriesRequest, StorageQueryInfo, TransactionInfo, VmAppCircuitInfo,
 };
@@ -13,44 +20,30 @@ pub fn prepare_request(
     storage_slots: Option<Vec<StorageSlotData>>,
     txs: Option<Vec<TransactionData>>,
     limits: Vec<u32>, // max receipts, max storage slots, max txs
+    api_key: &str,
 ) -> SendBatchQueriesRequest {
     let mut receipt_infos = vec![];
     if let Some(receipts) = receipts {
         receipt_infos = receipts
             .iter()
-            .map(|receipt| {
-                let log_extract_infos = receipt
-                    .fields
-                    .iter()
-                    .map(|log| LogExtractInfo {
-                        contract_address: log.contract.to_hex(),
-                        log_pos: log.log_pos as u64,
-                        log_topic0: log.topic.to_hex(),
-                        value_from_topic: log.is_topic,
-                        value_index: log.field_index as u64,
-                        value: log.value.to_string(),
-                        topics_length: 0, //deprecated
-                    })
-                    .collect::<Vec<_>>();
-
-                ReceiptInfo {
-                    transaction_hash: receipt.transaction_hash.to_hex(),
-                    log_extract_infos: log_extract_infos,
-                    blk_num: receipt.block_num as u64,
-                    receipt_index: receipt.mpt_key_path as u64,
-                }
+            .map(|receipt| ReceiptInfo {
+                transaction_hash: receipt.transaction_hash.to_hex(),
+                log_extract_infos: prepare_log_extract_infos(receipt),
+                blk_num: receipt.block_num as u64,
+                receipt_index: receipt.mpt_key_path as u64,
             })
             .collect::<Vec<_>>();
     }
 
     let mut storage_query_infos = vec![];
     if let Some(storage_slot) = storage_slots {
-        storage_query_infos = storage_slot
+        // consecutive slots of the same account and block share one query
+        storage_query_infos = AccountStorageData::group_storage_slots(&storage_slot)
             .iter()
-            .map(|slot| StorageQueryInfo {
-                account: slot.address.to_hex(),
-                storage_keys: vec![slot.slot.to_hex()],
-                blk_num: slot.block_num as u64,
+            .map(|account| StorageQueryInfo {
+                account: account.address.to_hex(),
+                storage_keys: account.slots.iter().map(|(slot, _)| slot.to_hex()).collect(),
+                blk_num: account.block_num as u64,
             })
             .collect::<Vec<_>>()
     }
@@ -89,7 +82,194 @@ pub fn prepare_request(
         queries: vec![query],
         target_chain_id: chain_id,
         option: 0, // zk mode
-        api_key: "TESTVM".to_string(),
+        api_key: api_key.to_string(),
         use_vm: true,
     }
 }
+
+/// One `LogExtractInfo` per log field of the receipt, e.g. the fields selected by a `ReceiptQuery`.
+pub fn prepare_log_extract_infos(receipt: &ReceiptData) -> Vec<LogExtractInfo> {
+    receipt
+        .fields
+        .iter()
+        .map(|log| LogExtractInfo {
+            contract_address: log.contract.to_hex(),
+            log_pos: log.log_pos as u64,
+            log_topic0: log.topic.to_hex(),
+            value_from_topic: log.is_topic,
+            value_index: log.field_index as u64,
+            value: log.value.to_string(),
+            topics_length: 0, //deprecated
+        })
+        .collect()
+}
+
+/// Builds the request proving the inputs saved by the SDK. The gateway has no block header or
+/// account query yet, so inputs with block header or account leaves, dummies included, are
+/// rejected, and it only proves `V0` leaves, so are inputs committed with `V1`.
+/// The output set by `SDK::set_output` is sent hex-encoded as the output of the circuit.
+pub fn prepare_sdk_request(
+    sdk: &SDK,
+    api_key: &str,
+) -> Result<SendBatchQueriesRequest, GatewayError> {
+    if sdk.max_block_header_size != 0 {
+        return Err(GatewayError::UnsupportedInput("block headers"));
+    }
+    if sdk.max_account_size != 0 {
+        return Err(GatewayError::UnsupportedInput("accounts"));
+    }
+    if sdk.commitment_version != CommitmentVersion::V0 {
+        return Err(GatewayError::UnsupportedInput("V1 commitments"));
+    }
+    let limits = vec![sdk.max_receipt_size, sdk.max_storage_size, sdk.max_tx_size];
+    let mut request = prepare_request(
+        sdk.chain_id,
+        sdk.receipts.clone(),
+        sdk.storage_slots.clone(),
+        sdk.transactions.clone(),
+        limits,
+        api_key,
+    );
+    if let Some(vm_app_info) = request.queries[0].vm_app_circuit_info.as_mut() {
+        if let Some(output) = &sdk.output {
+            vm_app_info.output = format!("0x{}", hex::encode(output));
+        }
+    }
+    Ok(request)
+}
+
+#[cfg(test)]
+mod test {
+    use alloy_sol_types::{sol, SolEvent};
+    use coprocessor_sdk::{
+        commitment::CommitmentVersion,
+        input_types::{event::EventField, storage::AccountStorageData},
+        query::{RawLog, RawReceipt, ReceiptQuery},
+        sdk::Builder,
+        Hex,
+    };
+    use crypto_bigint::U256;
+
+    use super::{prepare_log_extract_infos, prepare_sdk_request};
+    use crate::error::GatewayError;
+
+    #[test]
+    fn test_account_storage_shares_one_query() {
+        let account = AccountStorageData::add_account_storage(
+            21135753,
+            U256::from_u32(7),
+            1732158058,
+            [1; 20],
+            vec![([2; 32], U256::from_u32(3)), ([4; 32], U256::from_u32(5))],
+        );
+        let mut other = account.clone();
+        other.block_num += 1;
+        let sdk = Builder::new()
+            .with_chain_id(1)
+            .with_account_storage(vec![account, other])
+            .init(32, 32, 0);
+        assert_eq!(sdk.storage_slots.as_ref().unwrap().len(), 4);
+
+        let request = prepare_sdk_request(&sdk, "key").unwrap();
+        let storage_query_infos = &request.queries[0].storage_query_infos;
+        assert_eq!(storage_query_infos.len(), 2);
+        assert_eq!(storage_query_infos[0].storage_keys.len(), 2);
+        assert_eq!(storage_query_infos[1].blk_num, 21135754);
+    }
+
+    #[test]
+    fn test_query_drives_log_extract_infos() {
+        sol! {
+            event Transfer(address indexed from, address indexed to, uint256 value);
+        }
+        let token = [0xa0; 20];
+        let mut data = vec![0; 32];
+        data[31] = 100;
+        let raw_receipt = RawReceipt {
+            transaction_hash: [1; 32],
+            block_num: 21135753,
+            block_base_fee: U256::from_u32(7),
+            block_time: 1732158058,
+            mpt_key_path: 3,
+            logs: vec![RawLog {
+                address: token,
+                topics: vec![Transfer::SIGNATURE_HASH.0, [2; 32], [3; 32]],
+                data,
+                log_pos: 4,
+            }],
+        };
+        let receipts = ReceiptQuery::event::<Transfer>()
+            .contract(token)
+            .select([EventField::Data(0), EventField::Topic(2)])
+            .apply(&[raw_receipt])
+            .unwrap();
+
+        let infos = prepare_log_extract_infos(&receipts[0]);
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[0].contract_address, token.to_hex());
+        assert_eq!(infos[0].log_pos, 4);
+        assert_eq!(infos[0].log_topic0, Transfer::SIGNATURE_HASH.0.to_hex());
+        assert!(!infos[0].value_from_topic);
+        assert_eq!(infos[0].value, U256::from_u32(100).to_string());
+        assert!(infos[1].value_from_topic);
+        assert_eq!(infos[1].value_index, 2);
+    }
+
+    #[test]
+    fn test_output_is_sent_hex_encoded() {
+        sol! {
+            struct Volume {
+                uint256 volume;
+            }
+        }
+        let mut sdk = Builder::new().with_chain_id(1).init(32, 0, 0);
+        let request = prepare_sdk_request(&sdk, "key").unwrap();
+        assert!(request.queries[0].vm_app_circuit_info.as_ref().unwrap().output.is_empty());
+
+        let output = sdk.set_output(&Volume {
+            volume: alloy_primitives::U256::from(1000),
+        });
+        let request = prepare_sdk_request(&sdk, "key").unwrap();
+        assert_eq!(
+            request.queries[0].vm_app_circuit_info.as_ref().unwrap().output,
+            format!("0x{}", hex::encode(output))
+        );
+    }
+
+    #[test]
+    fn test_v1_commitments_are_rejected() {
+        let sdk = Builder::new()
+            .with_chain_id(1)
+            .with_commitment_version(CommitmentVersion::V1)
+            .init(32, 0, 0);
+        assert!(matches!(
+            prepare_sdk_request(&sdk, "key"),
+            Err(GatewayError::UnsupportedInput("V1 commitments"))
+        ));
+    }
+
+    #[test]
+    fn test_block_headers_are_rejected() {
+        let mut sdk = Builder::new().with_chain_id(1).init(32, 0, 0);
+        assert!(prepare_sdk_request(&sdk, "key").is_ok());
+
+        // dummy block header leaves can't be proven either
+        sdk.max_block_header_size = 32;
+        assert!(matches!(
+            prepare_sdk_request(&sdk, "key"),
+            Err(GatewayError::UnsupportedInput("block headers"))
+        ));
+    }
+
+    #[test]
+    fn test_accounts_are_rejected() {
+        let mut sdk = Builder::new().with_chain_id(1).init(32, 0, 0);
+
+        // dummy account leaves can't be proven either
+        sdk.max_account_size = 32;
+        assert!(matches!(
+            prepare_sdk_request(&sdk, "key"),
+            Err(GatewayError::UnsupportedInput("accounts"))
+        ));
+    }
+}
@@ -0,0 +1,128 @@
+use std::process::Command;
+
+use coprocessor_sdk::{
+    input_types::receipt::ReceiptData,
+    sdk::{Builder, SDK},
+};
+use crypto_bigint::U256;
+use vm_coprocessor_server::{
+    gateway::ErrCode,
+    mock::{mock_digest, MockGateway, MOCK_BATCH_ID},
+    CoprocessorClient, GatewayError,
+};
+
+fn test_sdk() -> SDK {
+    let receipt =
+        ReceiptData::add_receipt([1; 32], 21135753, U256::from_u32(7), 1732158058, 1, vec![]);
+    Builder::new()
+        .with_chain_id(1)
+        .with_receipts(vec![receipt; 2])
+        .init(32, 32, 0)
+}
+
+#[tokio::test]
+async fn test_submit() {
+    let gateway = MockGateway::new();
+    let server = gateway.clone().serve().await.unwrap();
+    let mut config = server.config();
+    config.api_key = "test-key".to_string();
+
+    let mut client = CoprocessorClient::connect(&config).await.unwrap();
+    let response = client.submit(&test_sdk()).await.unwrap();
+    assert_eq!(response.batch_id, MOCK_BATCH_ID);
+
+    let requests = gateway.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0], client.prepare_request(&test_sdk()).unwrap());
+    assert_eq!(requests[0].chain_id, 1);
+    assert_eq!(requests[0].api_key, "test-key");
+    assert_eq!(requests[0].queries[0].receipt_infos.len(), 2);
+}
+
+#[tokio::test]
+async fn test_submit_reports_gateway_error() {
+    let gateway = MockGateway::new()
+        .with_response("batch", vec![], 1)
+        .with_err(
+            ErrCode::ErrorCodeSlotValueProofGenerationFailed,
+            "slot 0 not found",
+        );
+    let server = gateway.serve().await.unwrap();
+
+    let mut client = CoprocessorClient::connect(&server.config()).await.unwrap();
+    let err = client.submit(&test_sdk()).await.unwrap_err();
+    assert!(matches!(
+        err,
+        GatewayError::SlotValueProofGenerationFailed(msg) if msg == "slot 0 not found"
+    ));
+}
+
+#[tokio::test]
+async fn test_get_circuit_digest() {
+    let server = MockGateway::new().serve().await.unwrap();
+
+    let mut client = CoprocessorClient::connect(&server.config()).await.unwrap();
+    assert_eq!(client.get_circuit_digest().await.unwrap(), mock_digest());
+}
+
+#[tokio::test]
+async fn test_cli_submit() {
+    let gateway = MockGateway::new();
+    let server = gateway.clone().serve().await.unwrap();
+    let dir = std::env::temp_dir().join(format!("brevis-request-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = test_sdk().save_inputs(dir.clone()).unwrap();
+
+    let endpoint = server.endpoint();
+    let output = tokio::task::spawn_blocking(move || {
+        Command::new(env!("CARGO_BIN_EXE_brevis-request"))
+            .args(["submit", "--json", "--insecure", "--endpoint", &endpoint])
+            .arg("--input")
+            .arg(&input)
+            .env_remove("BREVIS_GATEWAY_CONFIG")
+            .output()
+            .unwrap()
+    })
+    .await
+    .unwrap();
+    std::fs::remove_dir_all(dir).unwrap();
+
+    assert!(output.status.success());
+    let response: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(response["batch_id"], MOCK_BATCH_ID);
+    assert_eq!(gateway.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_cli_rejects_tampered_inputs() {
+    let gateway = MockGateway::new();
+    let server = gateway.clone().serve().await.unwrap();
+    let input = std::env::temp_dir().join(format!(
+        "brevis-request-tampered-{}.json",
+        std::process::id()
+    ));
+    test_sdk().save_inputs_as(&input).unwrap();
+    let mut file: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(&input).unwrap()).unwrap();
+    file["inputs"]["receipts"][0]["block_num"] = 21135754.into();
+    std::fs::write(&input, serde_json::to_vec(&file).unwrap()).unwrap();
+
+    let endpoint = server.endpoint();
+    let path = input.clone();
+    let output = tokio::task::spawn_blocking(move || {
+        Command::new(env!("CARGO_BIN_EXE_brevis-request"))
+            .args(["submit", "--insecure", "--endpoint", &endpoint])
+            .arg("--input")
+            .arg(&path)
+            .env_remove("BREVIS_GATEWAY_CONFIG")
+            .output()
+            .unwrap()
+    })
+    .await
+    .unwrap();
+    std::fs::remove_file(input).unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("checksum mismatch"));
+    assert!(gateway.requests().is_empty());
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data_types::hash_out::{MerkleInput, HASH_OUT},
+    poseidon2_hash::Poseidon2,
+};
+
+/// How the input leaves are committed. Versions coexist: inputs saved with one version keep
+/// their commitment, and the version is stored with them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CommitmentVersion {
+    /// Poseidon2 hash of the input elements alone, the commitment proven by the gateway
+    #[default]
+    V0,
+    /// Poseidon2 sponge initialized with the input type, the version and the element count,
+    /// so leaves of different types or lengths never share a preimage. The elements are the
+    /// canonical `ToFieldElements` encoding of the input, every field included. Not proven by
+    /// the gateway yet, so only for inputs checked locally.
+    V1,
+}
+
+impl CommitmentVersion {
+    pub fn number(&self) -> u32 {
+        match self {
+            CommitmentVersion::V0 => 0,
+            CommitmentVersion::V1 => 1,
+        }
+    }
+}
+
+/// The domain tag of each input type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputType {
+    Receipt = 1,
+    StorageSlot = 2,
+    Transaction = 3,
+    BlockHeader = 4,
+    Account = 5,
+}
+
+/// Commits the elements of an input with `version`.
+pub fn commit(input_type: InputType, version: CommitmentVersion, elements: &[u32]) -> MerkleInput {
+    match version {
+        CommitmentVersion::V0 => Poseidon2::<HASH_OUT>::hash(elements),
+        CommitmentVersion::V1 => {
+            let mut hasher = Poseidon2::<HASH_OUT>::with_domain(
+                input_type as u32,
+                version.number(),
+                elements.len() as u32,
+            );
+            for &element in elements {
+                hasher.update(element);
+            }
+            hasher.finalize()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{commit, CommitmentVersion, InputType};
+    use crate::{data_types::hash_out::HASH_OUT, poseidon2_hash::Poseidon2};
+
+    #[test]
+    fn test_domain_separation() {
+        let elements = [1, 2, 3];
+        let v0 = commit(InputType::Receipt, CommitmentVersion::V0, &elements);
+        assert_eq!(v0, Poseidon2::<HASH_OUT>::hash(&elements));
+        assert_eq!(
+            v0,
+            commit(InputType::StorageSlot, CommitmentVersion::V0, &elements)
+        );
+
+        let v1 = commit(InputType::Receipt, CommitmentVersion::V1, &elements);
+        assert_ne!(v1, v0);
+        assert_ne!(
+            v1,
+            commit(InputType::StorageSlot, CommitmentVersion::V1, &elements)
+        );
+        // the element count is bound
+        assert_ne!(
+            v1,
+            commit(InputType::Receipt, CommitmentVersion::V1, &[1, 2, 3, 0])
+        );
+        assert_ne!(
+            commit(InputType::Receipt, CommitmentVersion::V1, &[]),
+            commit(InputType::Receipt, CommitmentVersion::V1, &[0])
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use crypto_bigint::U256;
+
+/// Bytes packed per element. 24 bits stay below the BabyBear modulus, so no two inputs
+/// share an encoding.
+pub const BYTES_PER_ELEMENT: usize = 3;
+
+/// The canonical encoding of a value as BabyBear field elements, used by `V1` commitments.
+///
+/// | type | elements |
+/// | --- | --- |
+/// | `[u8; N]` (`Address`, `Bytes32`, selectors) | big-endian, 3 bytes per element, the last element takes the remaining 1 or 2 bytes |
+/// | `U256` | its 32 big-endian bytes: 11 elements |
+/// | `u64` | its 8 big-endian bytes: 3 elements |
+/// | `u32` | its 4 big-endian bytes: 2 elements |
+/// | `bool` | 1 element, 0 or 1 |
+/// | `Option<T>` | whether it is set as a `bool`, then the value if it is |
+/// | `[T]` | the length as a `u32`, then each item |
+/// | input structs | each field in declaration order |
+pub trait ToFieldElements {
+    /// Appends the elements of `self` to `elements`.
+    fn write_field_elements(&self, elements: &mut Vec<u32>);
+
+    fn to_field_elements(&self) -> Vec<u32> {
+        let mut elements = vec![];
+        self.write_field_elements(&mut elements);
+        elements
+    }
+}
+
+/// Packs big-endian `bytes` into elements of `BYTES_PER_ELEMENT` bytes.
+pub fn pack_bytes(bytes: &[u8], elements: &mut Vec<u32>) {
+    // index the bytes like `u256_to_u32_vec`, cheaper in the guest than folding them
+    for chunk in bytes.chunks(BYTES_PER_ELEMENT) {
+        elements.push(match chunk.len() {
+            3 => chunk[0] as u32 * 65536 + chunk[1] as u32 * 256 + chunk[2] as u32,
+            2 => chunk[0] as u32 * 256 + chunk[1] as u32,
+            _ => chunk[0] as u32,
+        });
+    }
+}
+
+impl<const N: usize> ToFieldElements for [u8; N] {
+    fn write_field_elements(&self, elements: &mut Vec<u32>) {
+        pack_bytes(self, elements);
+    }
+}
+
+impl ToFieldElements for U256 {
+    fn write_field_elements(&self, elements: &mut Vec<u32>) {
+        pack_bytes(&self.to_be_bytes(), elements);
+    }
+}
+
+impl ToFieldElements for u64 {
+    fn write_field_elements(&self, elements: &mut Vec<u32>) {
+        pack_bytes(&self.to_be_bytes(), elements);
+    }
+}
+
+impl ToFieldElements for u32 {
+    fn write_field_elements(&self, elements: &mut Vec<u32>) {
+        pack_bytes(&self.to_be_bytes(), elements);
+    }
+}
+
+impl ToFieldElements for bool {
+    fn write_field_elements(&self, elements: &mut Vec<u32>) {
+        elements.push(*self as u32);
+    }
+}
+
+impl<T: ToFieldElements> ToFieldElements for Option<T> {
+    fn write_field_elements(&self, elements: &mut Vec<u32>) {
+        self.is_some().write_field_elements(elements);
+        if let Some(value) = self {
+            value.write_field_elements(elements);
+        }
+    }
+}
+
+impl<T: ToFieldElements> ToFieldElements for [T] {
+    fn write_field_elements(&self, elements: &mut Vec<u32>) {
+        (self.len() as u32).write_field_elements(elements);
+        for item in self {
+            item.write_field_elements(elements);
+        }
+    }
+}
+
+impl<T: ToFieldElements> ToFieldElements for Vec<T> {
+    fn write_field_elements(&self, elements: &mut Vec<u32>) {
+        self.as_slice().write_field_elements(elements);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crypto_bigint::U256;
+
+    use super::ToFieldElements;
+    use crate::data_types::{address::Address, byte32::Bytes32};
+
+    #[test]
+    fn test_packing() {
+        let address: Address = core::array::from_fn(|i| i as u8 + 1);
+        assert_eq!(
+            address.to_field_elements(),
+            vec![0x010203, 0x040506, 0x070809, 0x0a0b0c, 0x0d0e0f, 0x101112, 0x1314]
+        );
+        let hash: Bytes32 = [0xff; 32];
+        let elements = hash.to_field_elements();
+        assert_eq!(elements.len(), 11);
+        assert_eq!(elements[..10], [0xffffff; 10]);
+        assert_eq!(elements[10], 0xffff);
+        assert_eq!(U256::MAX.to_field_elements(), elements);
+
+        assert_eq!(0x01020304u32.to_field_elements(), vec![0x010203, 0x04]);
+        assert_eq!(u32::MAX.to_field_elements(), vec![0xffffff, 0xff]);
+        assert_eq!(
+            0x0102030405060708u64.to_field_elements(),
+            vec![0x010203, 0x040506, 0x0708]
+        );
+        assert_eq!(
+            [0xa9u8, 0x05, 0x9c, 0xbb].to_field_elements(),
+            vec![0xa9059c, 0xbb]
+        );
+        assert_eq!(true.to_field_elements(), vec![1]);
+        assert_eq!(vec![true, false].to_field_elements(), vec![0, 2, 1, 0]);
+        assert_eq!(Vec::<u32>::new().to_field_elements(), vec![0, 0]);
+        assert_eq!(Some(0x01020304u32).to_field_elements(), vec![1, 0x010203, 0x04]);
+        assert_eq!(None::<u32>.to_field_elements(), vec![0]);
+    }
+}
@@ -2,4 +2,6 @@ pub mod byte32;
 
 pub mod address;
 
+pub mod field_elements;
+
 pub mod hash_out;
\ No newline at end of file
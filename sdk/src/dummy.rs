@@ -0,0 +1,298 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, RwLock},
+};
+
+use crypto_bigint::U256;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commitment::CommitmentVersion,
+    data_types::hash_out::MerkleInput,
+    error::SdkError,
+    input_types::{
+        account::AccountData, block_header::BlockHeaderData, receipt::ReceiptData,
+        storage::StorageSlotData, transaction::TransactionData,
+    },
+};
+
+/// Chains registered with the canonical dummy inputs by default.
+/// Other chains (L2s, testnets) are added with `DummyCommitment::register_chain`.
+pub const DEFAULT_CHAINS: [u64; 1] = [1];
+
+static REGISTRY: LazyLock<ChainRegistry> = LazyLock::new(ChainRegistry::new);
+
+/// The dummy inputs of each chain, and their commitments by chain and version, hashed on
+/// first use.
+struct ChainRegistry {
+    inputs: RwLock<HashMap<u64, DummyInputs>>,
+    commitments: RwLock<HashMap<(u64, CommitmentVersion), DummyCommitment>>,
+}
+
+impl ChainRegistry {
+    fn new() -> Self {
+        ChainRegistry {
+            inputs: RwLock::new(
+                DEFAULT_CHAINS
+                    .iter()
+                    .map(|&chain_id| (chain_id, DummyInputs::default()))
+                    .collect(),
+            ),
+            commitments: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn register(&self, chain_id: u64, dummies: DummyInputs) {
+        self.inputs.write().unwrap().insert(chain_id, dummies);
+        self.commitments
+            .write()
+            .unwrap()
+            .retain(|(id, _), _| *id != chain_id);
+    }
+
+    fn get(&self, chain_id: u64, version: CommitmentVersion) -> Result<DummyCommitment, SdkError> {
+        if let Some(commitment) = self.commitments.read().unwrap().get(&(chain_id, version)) {
+            return Ok(commitment.clone());
+        }
+        let commitment = self
+            .inputs
+            .read()
+            .unwrap()
+            .get(&chain_id)
+            .ok_or(SdkError::UnsupportedChain(chain_id))?
+            .commit_with(version);
+        self.commitments
+            .write()
+            .unwrap()
+            .insert((chain_id, version), commitment.clone());
+        Ok(commitment)
+    }
+
+    fn chains(&self) -> Vec<u64> {
+        let mut chains: Vec<u64> = self.inputs.read().unwrap().keys().copied().collect();
+        chains.sort_unstable();
+        chains
+    }
+}
+
+/// The inputs whose commitments pad each section of the input commitment tree.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DummyInputs {
+    pub receipt: ReceiptData,
+    pub storage_slot: StorageSlotData,
+    pub transaction: TransactionData,
+    pub block_header: BlockHeaderData,
+    pub account: AccountData,
+}
+
+impl Default for DummyInputs {
+    /// The canonical dummy inputs: every field zeroed and no log fields.
+    fn default() -> Self {
+        DummyInputs {
+            receipt: ReceiptData::add_receipt([0; 32], 0, U256::ZERO, 0, 0, vec![]),
+            storage_slot: StorageSlotData::add_storage_slot(
+                0,
+                U256::ZERO,
+                0,
+                [0; 20],
+                [0; 32],
+                U256::ZERO,
+            ),
+            transaction: TransactionData::add_transaction([0; 32], 0, U256::ZERO, 0, 0, [0; 32]),
+            block_header: BlockHeaderData::add_block_header(0, U256::ZERO, 0, [0; 32], [0; 32]),
+            account: AccountData::add_account([0; 20], 0, U256::ZERO, 0, [0; 32], [0; 32]),
+        }
+    }
+}
+
+impl DummyInputs {
+    pub fn commit(&self) -> DummyCommitment {
+        self.commit_with(CommitmentVersion::V0)
+    }
+
+    pub fn commit_with(&self, version: CommitmentVersion) -> DummyCommitment {
+        DummyCommitment {
+            receipt_dummy_commitment: self.receipt.commit_inputs_with(version),
+            storage_dummy_commitment: self.storage_slot.commit_inputs_with(version),
+            tx_dummy_commitment: self.transaction.commit_inputs_with(version),
+            block_header_dummy_commitment: self.block_header.commit_inputs_with(version),
+            account_dummy_commitment: self.account.commit_inputs_with(version),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DummyCommitment {
+    pub receipt_dummy_commitment: MerkleInput,
+    pub storage_dummy_commitment: MerkleInput,
+    pub tx_dummy_commitment: MerkleInput,
+    /// Missing in inputs saved before block headers were supported
+    #[serde(default)]
+    pub block_header_dummy_commitment: MerkleInput,
+    /// Missing in inputs saved before accounts were supported
+    #[serde(default)]
+    pub account_dummy_commitment: MerkleInput,
+}
+
+impl DummyCommitment {
+    /// Registers `chain_id` with the commitments of `dummies`, replacing any existing entry.
+    pub fn register_chain(chain_id: u64, dummies: DummyInputs) -> DummyCommitment {
+        let commitment = dummies.commit();
+        REGISTRY.register(chain_id, dummies);
+        commitment
+    }
+
+    pub fn try_get_dummy_commitment(chain_id: u64) -> Result<DummyCommitment, SdkError> {
+        Self::try_get_dummy_commitment_with(chain_id, CommitmentVersion::V0)
+    }
+
+    pub fn try_get_dummy_commitment_with(
+        chain_id: u64,
+        version: CommitmentVersion,
+    ) -> Result<DummyCommitment, SdkError> {
+        REGISTRY.get(chain_id, version)
+    }
+
+    /// Same as `try_get_dummy_commitment`, but panics if the chain is not registered.
+    pub fn get_dummy_commitment(chain_id: u64) -> DummyCommitment {
+        Self::try_get_dummy_commitment(chain_id).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// The registered chains, the default ones included.
+    pub fn supported_chains() -> Vec<u64> {
+        REGISTRY.chains()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ChainRegistry, DummyCommitment, DummyInputs};
+    use crate::{commitment::CommitmentVersion, error::SdkError};
+
+    #[test]
+    fn test_chain_1_dummy_commitments() {
+        let dummy = DummyCommitment::get_dummy_commitment(1);
+        assert_eq!(dummy, DummyInputs::default().commit());
+        assert_eq!(
+            dummy.receipt_dummy_commitment,
+            [
+                29700337, 590638609, 1973260080, 1814627189, 1308393548, 452723154, 210515557,
+                1545429411
+            ]
+        );
+        assert_eq!(
+            dummy.storage_dummy_commitment,
+            [
+                1258285048, 751718890, 893742426, 760792654, 1260252068, 56697570, 1787948562,
+                1522089700
+            ]
+        );
+        assert_eq!(
+            dummy.tx_dummy_commitment,
+            [
+                254353142, 302608791, 1634765024, 101068611, 1957459604, 151774004, 150751598,
+                1212057621
+            ]
+        );
+        assert_eq!(
+            dummy.block_header_dummy_commitment,
+            [
+                1496990956, 228511364, 1240622394, 1160502827, 1522693155, 1405286782, 556774900,
+                739156075
+            ]
+        );
+        assert_eq!(
+            dummy.account_dummy_commitment,
+            [
+                1867696938, 1959494515, 264318565, 1260649254, 1654628288, 802147703, 30109870,
+                769496295
+            ]
+        );
+    }
+
+    #[test]
+    fn test_register_chain() {
+        let registry = ChainRegistry::new();
+        assert_eq!(registry.chains(), vec![1]);
+        assert_eq!(
+            registry.get(11155111, CommitmentVersion::V0),
+            Err(SdkError::UnsupportedChain(11155111))
+        );
+
+        let mut dummies = DummyInputs::default();
+        dummies.receipt.block_num = 1;
+        registry.register(11155111, dummies.clone());
+        assert_eq!(registry.chains(), vec![1, 11155111]);
+        let commitment = registry.get(11155111, CommitmentVersion::V0).unwrap();
+        assert_eq!(commitment, dummies.commit());
+        assert_ne!(
+            commitment.receipt_dummy_commitment,
+            DummyInputs::default().commit().receipt_dummy_commitment
+        );
+        assert_eq!(
+            commitment.storage_dummy_commitment,
+            DummyInputs::default().commit().storage_dummy_commitment
+        );
+
+        // registering again drops the commitments hashed for the previous inputs
+        registry.register(11155111, DummyInputs::default());
+        assert_eq!(
+            registry.get(11155111, CommitmentVersion::V0).unwrap(),
+            DummyInputs::default().commit()
+        );
+    }
+
+    #[test]
+    fn test_chain_1_v1_dummy_commitments() {
+        let dummy =
+            DummyCommitment::try_get_dummy_commitment_with(1, CommitmentVersion::V1).unwrap();
+        assert_eq!(
+            dummy,
+            DummyInputs::default().commit_with(CommitmentVersion::V1)
+        );
+        assert_eq!(
+            dummy.receipt_dummy_commitment,
+            [
+                696103988, 1434306152, 1114600526, 1257336038, 532084567, 455124364, 754000031,
+                1611096621
+            ]
+        );
+        assert_eq!(
+            dummy.storage_dummy_commitment,
+            [
+                280393935, 1944064716, 286347032, 1362082543, 1971270302, 1361323629, 767396325,
+                805535073
+            ]
+        );
+        assert_eq!(
+            dummy.tx_dummy_commitment,
+            [
+                1993504789, 1495689671, 1656013376, 182999063, 1119730995, 626238127, 1765208946,
+                501457629
+            ]
+        );
+        assert_eq!(
+            dummy.block_header_dummy_commitment,
+            [
+                925376020, 1993019289, 964965886, 1749052029, 563859503, 1641195971, 1383813468,
+                388633309
+            ]
+        );
+        assert_eq!(
+            dummy.account_dummy_commitment,
+            [
+                174658255, 1299030931, 935907714, 1905719258, 1233525029, 1324412426, 508145927,
+                1784043953
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_chain() {
+        assert_eq!(DummyCommitment::supported_chains(), vec![1]);
+        assert_eq!(
+            DummyCommitment::try_get_dummy_commitment(11155111),
+            Err(SdkError::UnsupportedChain(11155111))
+        );
+    }
+}
@@ -0,0 +1,26 @@
+use thiserror::Error;
+
+/// Errors returned when the SDK inputs do not fit the requested layout.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SdkError {
+    #[error("receipts length {len} exceeds the max receipt size {max}")]
+    TooManyReceipts { len: usize, max: u32 },
+    #[error("storage slots length {len} exceeds the max storage size {max}")]
+    TooManyStorageSlots { len: usize, max: u32 },
+    #[error("transactions length {len} exceeds the max transaction size {max}")]
+    TooManyTransactions { len: usize, max: u32 },
+    #[error("block headers length {len} exceeds the max block header size {max}")]
+    TooManyBlockHeaders { len: usize, max: u32 },
+    #[error("accounts length {len} exceeds the max account size {max}")]
+    TooManyAccounts { len: usize, max: u32 },
+    #[error("max {category} size {size} is not a multiple of 32")]
+    SizeNotMultipleOf32 { category: &'static str, size: u32 },
+    #[error("total max size {0} is not a power of two")]
+    LeafCountNotPowerOfTwo(u64),
+    #[error("chain id is not set")]
+    MissingChainId,
+    #[error("chain {0} is not supported, register it with `DummyCommitment::register_chain`")]
+    UnsupportedChain(u64),
+    #[error("invalid output encoding: {0}")]
+    InvalidOutput(String),
+}
@@ -0,0 +1,414 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use alloy_primitives::keccak256;
+use crypto_bigint::U256;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::{
+    data_types::{address::Address, byte32::Bytes32},
+    input_types::{receipt::ReceiptData, storage::StorageSlotData, transaction::TransactionData},
+    mpt::index_trie_proof,
+    query::{QueryError, RawLog, RawReceipt, RawTransaction, ReceiptQuery},
+    Hex,
+};
+
+#[derive(Debug, Error)]
+pub enum FetchError {
+    #[error("request to the node failed: {0}")]
+    Http(#[from] Box<ureq::Error>),
+    #[error("failed to read the node response: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("node returned error {code}: {message}")]
+    Rpc { code: i64, message: String },
+    #[error("{0} not found")]
+    NotFound(String),
+    #[error("invalid {field} in the node response")]
+    InvalidResponse { field: &'static str },
+    #[error("the transactions of block {0} don't match its transactions root")]
+    TransactionsRootMismatch(u32),
+    #[error(transparent)]
+    Query(#[from] QueryError),
+}
+
+/// Fills the SDK inputs from an Ethereum node with the standard JSON-RPC methods.
+///
+/// The transaction methods also read the raw transactions of their block with
+/// `eth_getRawTransactionByBlockNumberAndIndex`, which is not in the standard API but served
+/// by geth, erigon and reth, to rebuild its transactions trie. A block and its raw
+/// transactions are read once per fetcher.
+///
+/// ```ignore
+/// let fetcher = RpcFetcher::new("http://localhost:8545");
+/// let receipts = fetcher.receipts(&[tx_hash], &ReceiptQuery::event::<Swap>().select(fields))?;
+/// let slot = fetcher.storage_slot(pool, [0; 32], 21756846)?;
+/// ```
+pub struct RpcFetcher {
+    url: String,
+    agent: ureq::Agent,
+    next_id: AtomicU64,
+    // base fee and time by block number
+    blocks: Mutex<HashMap<u32, (U256, u32)>>,
+    // raw transactions by block number, checked against the transactions root
+    transactions: Mutex<HashMap<u32, Vec<Vec<u8>>>>,
+}
+
+impl RpcFetcher {
+    pub fn new(url: impl Into<String>) -> Self {
+        RpcFetcher {
+            url: url.into(),
+            agent: ureq::Agent::new(),
+            next_id: AtomicU64::new(1),
+            blocks: Mutex::new(HashMap::new()),
+            transactions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Calls `method` and returns its result, `null` included.
+    pub fn call(&self, method: &str, params: Value) -> Result<Value, FetchError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        let mut response: Value = self
+            .agent
+            .post(&self.url)
+            .send_json(request)
+            .map_err(Box::new)?
+            .into_json()?;
+        if let Some(error) = response.get("error") {
+            return Err(FetchError::Rpc {
+                code: error
+                    .get("code")
+                    .and_then(Value::as_i64)
+                    .unwrap_or_default(),
+                message: error
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+            });
+        }
+        Ok(response
+            .get_mut("result")
+            .ok_or(FetchError::InvalidResponse { field: "result" })?
+            .take())
+    }
+
+    /// The base fee and the timestamp of a block, the base fee being zero before London.
+    pub fn block(&self, block_num: u32) -> Result<(U256, u32), FetchError> {
+        if let Some(block) = self.blocks.lock().unwrap().get(&block_num) {
+            return Ok(*block);
+        }
+        let block = self.call(
+            "eth_getBlockByNumber",
+            json!([format!("{:#x}", block_num), false]),
+        )?;
+        if block.is_null() {
+            return Err(FetchError::NotFound(format!("block {}", block_num)));
+        }
+        let base_fee = match block.get("baseFeePerGas") {
+            Some(Value::Null) | None => U256::ZERO,
+            Some(_) => u256_field(&block, "baseFeePerGas")?,
+        };
+        let block_time = u32_field(&block, "timestamp")?;
+        self.blocks
+            .lock()
+            .unwrap()
+            .insert(block_num, (base_fee, block_time));
+        Ok((base_fee, block_time))
+    }
+
+    /// The receipt of a transaction with all its logs. `log_pos` is the position of a log in
+    /// the receipt, not its `logIndex` in the block, and `mpt_key_path` the index of the
+    /// transaction in its block.
+    pub fn raw_receipt(&self, tx_hash: Bytes32) -> Result<RawReceipt, FetchError> {
+        let receipt = self.call("eth_getTransactionReceipt", json!([tx_hash.to_hex()]))?;
+        if receipt.is_null() {
+            return Err(FetchError::NotFound(format!(
+                "receipt {}",
+                tx_hash.to_hex()
+            )));
+        }
+        let block_num = u32_field(&receipt, "blockNumber")?;
+        let (block_base_fee, block_time) = self.block(block_num)?;
+        let logs = receipt
+            .get("logs")
+            .and_then(Value::as_array)
+            .ok_or(FetchError::InvalidResponse { field: "logs" })?
+            .iter()
+            .enumerate()
+            .map(|(log_pos, log)| {
+                let topics = log
+                    .get("topics")
+                    .and_then(Value::as_array)
+                    .ok_or(FetchError::InvalidResponse { field: "topics" })?
+                    .iter()
+                    .map(|topic| {
+                        topic
+                            .as_str()
+                            .and_then(|topic| Bytes32::from_hex(topic).ok())
+                            .ok_or(FetchError::InvalidResponse { field: "topics" })
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(RawLog {
+                    address: address_field(log, "address")?,
+                    topics,
+                    data: bytes_field(log, "data")?,
+                    log_pos: log_pos as u32,
+                })
+            })
+            .collect::<Result<_, FetchError>>()?;
+        Ok(RawReceipt {
+            transaction_hash: tx_hash,
+            block_num,
+            block_base_fee,
+            block_time,
+            mpt_key_path: u32_field(&receipt, "transactionIndex")?,
+            logs,
+        })
+    }
+
+    /// Fetches the receipts of `tx_hashes` and selects their fields with `query`.
+    pub fn receipts(
+        &self,
+        tx_hashes: &[Bytes32],
+        query: &ReceiptQuery,
+    ) -> Result<Vec<ReceiptData>, FetchError> {
+        let raw_receipts = tx_hashes
+            .iter()
+            .map(|tx_hash| self.raw_receipt(*tx_hash))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(query.apply(&raw_receipts)?)
+    }
+
+    pub fn storage_slot(
+        &self,
+        address: Address,
+        slot: Bytes32,
+        block_num: u32,
+    ) -> Result<StorageSlotData, FetchError> {
+        let value = self.call(
+            "eth_getStorageAt",
+            json!([address.to_hex(), slot.to_hex(), format!("{:#x}", block_num)]),
+        )?;
+        let value = value
+            .as_str()
+            .and_then(|value| parse_u256(value).ok())
+            .ok_or(FetchError::InvalidResponse {
+                field: "storage value",
+            })?;
+        let (block_base_fee, block_time) = self.block(block_num)?;
+        Ok(StorageSlotData::add_storage_slot(
+            block_num,
+            block_base_fee,
+            block_time,
+            address,
+            slot,
+            value,
+        ))
+    }
+
+    /// The raw transactions of a block, checked against its transactions root.
+    pub fn block_transactions(&self, block_num: u32) -> Result<Vec<Vec<u8>>, FetchError> {
+        if let Some(txs) = self.transactions.lock().unwrap().get(&block_num) {
+            return Ok(txs.clone());
+        }
+        let block = self.call(
+            "eth_getBlockByNumber",
+            json!([format!("{:#x}", block_num), false]),
+        )?;
+        if block.is_null() {
+            return Err(FetchError::NotFound(format!("block {}", block_num)));
+        }
+        let count = block
+            .get("transactions")
+            .and_then(Value::as_array)
+            .ok_or(FetchError::InvalidResponse {
+                field: "transactions",
+            })?
+            .len();
+        let txs = (0..count)
+            .map(|index| {
+                let tx = self.call(
+                    "eth_getRawTransactionByBlockNumberAndIndex",
+                    json!([format!("{:#x}", block_num), format!("{:#x}", index)]),
+                )?;
+                tx.as_str()
+                    .and_then(|tx| hex::decode(tx.trim_start_matches("0x")).ok())
+                    .ok_or(FetchError::InvalidResponse {
+                        field: "raw transaction",
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let (root, _) = index_trie_proof(&txs, 0);
+        let transactions_root =
+            Bytes32::from_hex(str_field(&block, "transactionsRoot")?).map_err(|_| {
+                FetchError::InvalidResponse {
+                    field: "transactionsRoot",
+                }
+            })?;
+        if root != transactions_root {
+            return Err(FetchError::TransactionsRootMismatch(block_num));
+        }
+        self.transactions
+            .lock()
+            .unwrap()
+            .insert(block_num, txs.clone());
+        Ok(txs)
+    }
+
+    /// The proof of the transaction at `index` in the transactions trie of a block, built
+    /// from all its transactions, for `BlockHeader::verify_transaction`.
+    pub fn transaction_proof(
+        &self,
+        block_num: u32,
+        index: u32,
+    ) -> Result<Vec<Vec<u8>>, FetchError> {
+        let txs = self.block_transactions(block_num)?;
+        if index as usize >= txs.len() {
+            return Err(FetchError::NotFound(format!(
+                "transaction {} of block {}",
+                index, block_num
+            )));
+        }
+        Ok(index_trie_proof(&txs, index).1)
+    }
+
+    /// A transaction with its leaf hash, the hash of the last node of its proof.
+    pub fn raw_transaction(&self, tx_hash: Bytes32) -> Result<RawTransaction, FetchError> {
+        let tx = self.call("eth_getTransactionByHash", json!([tx_hash.to_hex()]))?;
+        if tx.is_null() {
+            return Err(FetchError::NotFound(format!(
+                "transaction {}",
+                tx_hash.to_hex()
+            )));
+        }
+        let block_num = match tx.get("blockNumber") {
+            Some(Value::Null) | None => {
+                return Err(FetchError::NotFound(format!(
+                    "block of pending transaction {}",
+                    tx_hash.to_hex()
+                )))
+            }
+            Some(_) => u32_field(&tx, "blockNumber")?,
+        };
+        let (block_base_fee, block_time) = self.block(block_num)?;
+        let mpt_key_path = u32_field(&tx, "transactionIndex")?;
+        let txs = self.block_transactions(block_num)?;
+        match txs.get(mpt_key_path as usize) {
+            Some(raw) if keccak256(raw).0 == tx_hash => {}
+            _ => {
+                return Err(FetchError::InvalidResponse {
+                    field: "transactionIndex",
+                })
+            }
+        }
+        let (_, proof) = index_trie_proof(&txs, mpt_key_path);
+        let leaf_hash = keccak256(proof.last().unwrap()).0;
+
+        // `to` is null for a contract creation
+        let to = match tx.get("to") {
+            Some(Value::Null) | None => None,
+            Some(_) => Some(address_field(&tx, "to")?),
+        };
+        let (gas_price, gas_tip_cap) = match tx.get("maxFeePerGas") {
+            Some(Value::Null) | None => (u256_field(&tx, "gasPrice")?, U256::ZERO),
+            Some(_) => (
+                u256_field(&tx, "maxFeePerGas")?,
+                u256_field(&tx, "maxPriorityFeePerGas")?,
+            ),
+        };
+        Ok(RawTransaction {
+            hash: tx_hash,
+            block_num,
+            block_base_fee,
+            block_time,
+            mpt_key_path,
+            leaf_hash,
+            from: address_field(&tx, "from")?,
+            to,
+            value: u256_field(&tx, "value")?,
+            nonce: u64_field(&tx, "nonce")?,
+            gas_price,
+            gas_tip_cap,
+            input: bytes_field(&tx, "input")?,
+        })
+    }
+
+    /// A transaction with the calldata words at `calldata_words`.
+    pub fn transaction(
+        &self,
+        tx_hash: Bytes32,
+        calldata_words: &[u32],
+    ) -> Result<TransactionData, FetchError> {
+        Ok(self
+            .raw_transaction(tx_hash)?
+            .transaction_data(calldata_words)?)
+    }
+}
+
+fn str_field<'a>(value: &'a Value, field: &'static str) -> Result<&'a str, FetchError> {
+    value
+        .get(field)
+        .and_then(Value::as_str)
+        .ok_or(FetchError::InvalidResponse { field })
+}
+
+fn u64_field(value: &Value, field: &'static str) -> Result<u64, FetchError> {
+    let quantity = str_field(value, field)?;
+    quantity
+        .strip_prefix("0x")
+        .and_then(|digits| u64::from_str_radix(digits, 16).ok())
+        .ok_or(FetchError::InvalidResponse { field })
+}
+
+fn u32_field(value: &Value, field: &'static str) -> Result<u32, FetchError> {
+    u64_field(value, field)?
+        .try_into()
+        .map_err(|_| FetchError::InvalidResponse { field })
+}
+
+fn u256_field(value: &Value, field: &'static str) -> Result<U256, FetchError> {
+    parse_u256(str_field(value, field)?).map_err(|_| FetchError::InvalidResponse { field })
+}
+
+fn address_field(value: &Value, field: &'static str) -> Result<Address, FetchError> {
+    Address::from_hex(str_field(value, field)?).map_err(|_| FetchError::InvalidResponse { field })
+}
+
+fn bytes_field(value: &Value, field: &'static str) -> Result<Vec<u8>, FetchError> {
+    let data = str_field(value, field)?;
+    hex::decode(data.trim_start_matches("0x")).map_err(|_| FetchError::InvalidResponse { field })
+}
+
+/// Parses a hex quantity or a 32-byte word, both `0x`-prefixed.
+fn parse_u256(value: &str) -> Result<U256, ()> {
+    let digits = value.strip_prefix("0x").ok_or(())?;
+    if digits.is_empty() || digits.len() > 64 {
+        return Err(());
+    }
+    let bytes = hex::decode(format!("{:0>64}", digits)).map_err(|_| ())?;
+    Ok(U256::from_be_slice(&bytes))
+}
+
+#[cfg(test)]
+mod test {
+    use crypto_bigint::U256;
+
+    use super::parse_u256;
+
+    #[test]
+    fn test_parse_u256() {
+        assert_eq!(parse_u256("0x0"), Ok(U256::ZERO));
+        assert_eq!(parse_u256("0x1a2"), Ok(U256::from_u32(0x1a2)));
+        assert_eq!(parse_u256(&format!("0x{}", "f".repeat(64))), Ok(U256::MAX));
+        assert!(parse_u256("0x").is_err());
+        assert!(parse_u256("1a2").is_err());
+        assert!(parse_u256(&format!("0x{}", "f".repeat(65))).is_err());
+    }
+}
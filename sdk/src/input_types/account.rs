@@ -0,0 +1,107 @@
+use crypto_bigint::U256;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commitment::{commit, CommitmentVersion, InputType},
+    data_types::{
+        address::Address, byte32::Bytes32, field_elements::ToFieldElements, hash_out::MerkleInput,
+    },
+};
+
+/// The state of an account at a block.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountData {
+    pub address: Address,
+    pub block_num: u32,
+    pub balance: U256,
+    pub nonce: u64,
+    pub code_hash: Bytes32,
+    pub storage_root: Bytes32,
+}
+
+impl AccountData {
+    pub fn add_account(
+        address: Address,
+        block_num: u32,
+        balance: U256,
+        nonce: u64,
+        code_hash: Bytes32,
+        storage_root: Bytes32,
+    ) -> Self {
+        AccountData {
+            address,
+            block_num,
+            balance,
+            nonce,
+            code_hash,
+            storage_root,
+        }
+    }
+
+    pub fn commit_inputs(&self) -> MerkleInput {
+        self.commit_inputs_with(CommitmentVersion::V0)
+    }
+
+    pub fn commit_inputs_with(&self, version: CommitmentVersion) -> MerkleInput {
+        let elements = match version {
+            CommitmentVersion::V0 => self.v0_elements(),
+            CommitmentVersion::V1 => self.to_field_elements(),
+        };
+        commit(InputType::Account, version, &elements)
+    }
+
+    /// The elements committed by `V0`. The gateway has no account query, so these leaves
+    /// can't be proven yet.
+    fn v0_elements(&self) -> Vec<u32> {
+        let mut inputs: Vec<u32> = vec![];
+        let address_elements = self
+            .address
+            .chunks_exact(2)
+            .map(|chunk| (chunk[0] as u32) * 256 + chunk[1] as u32)
+            .collect::<Vec<_>>();
+        inputs.extend(address_elements);
+        inputs.push(self.block_num);
+
+        let balance_elements = self
+            .balance
+            .to_be_bytes()
+            .chunks_exact(2)
+            .map(|chunk| (chunk[0] as u32) * 256 + chunk[1] as u32)
+            .collect::<Vec<_>>();
+        inputs.extend(balance_elements);
+
+        let nonce_elements = self
+            .nonce
+            .to_be_bytes()
+            .chunks_exact(2)
+            .map(|chunk| (chunk[0] as u32) * 256 + chunk[1] as u32)
+            .collect::<Vec<_>>();
+        inputs.extend(nonce_elements);
+
+        let code_hash_elements = self
+            .code_hash
+            .chunks_exact(2)
+            .map(|chunk| (chunk[0] as u32) * 256 + chunk[1] as u32)
+            .collect::<Vec<_>>();
+        inputs.extend(code_hash_elements);
+
+        let storage_root_elements = self
+            .storage_root
+            .chunks_exact(2)
+            .map(|chunk| (chunk[0] as u32) * 256 + chunk[1] as u32)
+            .collect::<Vec<_>>();
+        inputs.extend(storage_root_elements);
+        inputs
+    }
+}
+
+impl ToFieldElements for AccountData {
+    fn write_field_elements(&self, elements: &mut Vec<u32>) {
+        self.address.write_field_elements(elements);
+        self.block_num.write_field_elements(elements);
+        self.balance.write_field_elements(elements);
+        self.nonce.write_field_elements(elements);
+        self.code_hash.write_field_elements(elements);
+        self.storage_root.write_field_elements(elements);
+    }
+}
@@ -0,0 +1,85 @@
+use crypto_bigint::U256;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commitment::{commit, CommitmentVersion, InputType},
+    data_types::{byte32::Bytes32, field_elements::ToFieldElements, hash_out::MerkleInput},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockHeaderData {
+    pub block_num: u32,
+    pub block_base_fee: U256,
+    pub block_time: u32,
+    pub hash: Bytes32,
+    pub parent_hash: Bytes32,
+}
+
+impl BlockHeaderData {
+    pub fn add_block_header(
+        block_num: u32,
+        block_base_fee: U256,
+        block_time: u32,
+        hash: Bytes32,
+        parent_hash: Bytes32,
+    ) -> Self {
+        BlockHeaderData {
+            block_num,
+            block_base_fee,
+            block_time,
+            hash,
+            parent_hash,
+        }
+    }
+
+    pub fn commit_inputs(&self) -> MerkleInput {
+        self.commit_inputs_with(CommitmentVersion::V0)
+    }
+
+    pub fn commit_inputs_with(&self, version: CommitmentVersion) -> MerkleInput {
+        let elements = match version {
+            CommitmentVersion::V0 => self.v0_elements(),
+            CommitmentVersion::V1 => self.to_field_elements(),
+        };
+        commit(InputType::BlockHeader, version, &elements)
+    }
+
+    /// The elements committed by `V0`. The gateway has no block header query, so these
+    /// leaves can't be proven yet.
+    fn v0_elements(&self) -> Vec<u32> {
+        let mut inputs: Vec<u32> = vec![];
+        inputs.push(self.block_num);
+        let base_fee_le = self.block_base_fee.to_be_bytes();
+        let base_fee_elements = base_fee_le
+            .chunks_exact(2)
+            .map(|chunk| (chunk[0] as u32) * 256 + chunk[1] as u32)
+            .collect::<Vec<_>>();
+        inputs.extend(base_fee_elements);
+        inputs.push(self.block_time);
+
+        let hash_elements = self
+            .hash
+            .chunks_exact(2)
+            .map(|chunk| (chunk[0] as u32) * 256 + chunk[1] as u32)
+            .collect::<Vec<_>>();
+        inputs.extend(hash_elements);
+
+        let parent_hash_elements = self
+            .parent_hash
+            .chunks_exact(2)
+            .map(|chunk| (chunk[0] as u32) * 256 + chunk[1] as u32)
+            .collect::<Vec<_>>();
+        inputs.extend(parent_hash_elements);
+        inputs
+    }
+}
+
+impl ToFieldElements for BlockHeaderData {
+    fn write_field_elements(&self, elements: &mut Vec<u32>) {
+        self.block_num.write_field_elements(elements);
+        self.block_base_fee.write_field_elements(elements);
+        self.block_time.write_field_elements(elements);
+        self.hash.write_field_elements(elements);
+        self.parent_hash.write_field_elements(elements);
+    }
+}
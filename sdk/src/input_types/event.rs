@@ -0,0 +1,247 @@
+use std::marker::PhantomData;
+
+use alloy_sol_types::{SolEvent, SolType, TopicList};
+use crypto_bigint::U256;
+use thiserror::Error;
+
+use crate::data_types::{address::Address, byte32::Bytes32};
+
+use super::receipt::{LogFieldData, ReceiptData};
+
+/// Where a value is read from in a log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventField {
+    /// `topics[i]`, `topics[0]` being the event signature, so the first indexed parameter is 1
+    Topic(u32),
+    /// The i-th 32-byte word of the log data
+    Data(u32),
+}
+
+impl EventField {
+    fn matches(&self, field: &LogFieldData) -> bool {
+        match *self {
+            EventField::Topic(index) => field.is_topic && field.field_index == index,
+            EventField::Data(index) => !field.is_topic && field.field_index == index,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum EventError {
+    #[error("fields of log {log_pos} disagree on the contract or the event topic")]
+    InconsistentLog { log_pos: u32 },
+    #[error("{event} log {log_pos} is emitted by an unexpected contract")]
+    ContractMismatch { event: &'static str, log_pos: u32 },
+    #[error("no {event} log in the receipt")]
+    EventNotFound { event: &'static str },
+    #[error("{event} has no topic {index}")]
+    TopicOutOfRange { event: &'static str, index: u32 },
+    #[error("{field:?} of log {log_pos} is not extracted")]
+    MissingField { log_pos: u32, field: EventField },
+    #[error("{field:?} of log {log_pos} is not a valid {sol_type}")]
+    InvalidValue {
+        log_pos: u32,
+        field: EventField,
+        sol_type: &'static str,
+    },
+}
+
+/// The extracted fields of one log, checked to be an `E` event.
+///
+/// `V0` commitments, the only ones the gateway proves, bind the first 6 bytes of the event
+/// topic, so under `V0` the event is only proven up to a 6-byte prefix of its signature
+/// hash. The contract is bound in full, so check it too.
+#[derive(Debug, Clone)]
+pub struct EventLog<'a, E> {
+    pub contract: Address,
+    pub log_pos: u32,
+    pub fields: Vec<&'a LogFieldData>,
+    event: PhantomData<E>,
+}
+
+impl<E: SolEvent> EventLog<'_, E> {
+    /// The raw word of `field`.
+    pub fn value(&self, field: EventField) -> Result<U256, EventError> {
+        if let EventField::Topic(index) = field {
+            if index as usize >= <E::TopicList as TopicList>::COUNT {
+                return Err(EventError::TopicOutOfRange {
+                    event: E::SIGNATURE,
+                    index,
+                });
+            }
+        }
+        self.fields
+            .iter()
+            .find(|d| field.matches(d))
+            .map(|d| d.value)
+            .ok_or(EventError::MissingField {
+                log_pos: self.log_pos,
+                field,
+            })
+    }
+
+    /// Decodes `field` as a single-word Solidity type, e.g. `sol_data::Address` or `sol_data::Uint<256>`.
+    pub fn get<T: SolType>(&self, field: EventField) -> Result<T::RustType, EventError> {
+        let word = self.value(field)?.to_be_bytes();
+        T::abi_decode(&word, true).map_err(|_| EventError::InvalidValue {
+            log_pos: self.log_pos,
+            field,
+            sol_type: T::SOL_NAME,
+        })
+    }
+}
+
+impl ReceiptData {
+    /// Groups the fields by log, in order of first appearance, checking that the fields of
+    /// a log agree on the contract and the event topic.
+    pub fn logs(&self) -> Result<Vec<Vec<&LogFieldData>>, EventError> {
+        let mut logs: Vec<Vec<&LogFieldData>> = vec![];
+        for field in &self.fields {
+            match logs.iter_mut().find(|log| log[0].log_pos == field.log_pos) {
+                Some(log) => {
+                    if log[0].contract != field.contract || log[0].topic != field.topic {
+                        return Err(EventError::InconsistentLog {
+                            log_pos: field.log_pos,
+                        });
+                    }
+                    log.push(field);
+                }
+                None => logs.push(vec![field]),
+            }
+        }
+        Ok(logs)
+    }
+
+    /// The logs whose topic is the signature of `E`. If `contract` is set, every such log must
+    /// be emitted by it. The full topic is compared, but only its first 6 bytes are proven
+    /// under `V0`, see `EventLog`.
+    pub fn events<E: SolEvent>(
+        &self,
+        contract: Option<Address>,
+    ) -> Result<Vec<EventLog<'_, E>>, EventError> {
+        let topic: Bytes32 = E::SIGNATURE_HASH.0;
+        let mut events = vec![];
+        for fields in self.logs()? {
+            let (log_contract, log_pos) = (fields[0].contract, fields[0].log_pos);
+            if fields[0].topic != topic {
+                continue;
+            }
+            if contract.is_some_and(|contract| contract != log_contract) {
+                return Err(EventError::ContractMismatch {
+                    event: E::SIGNATURE,
+                    log_pos,
+                });
+            }
+            events.push(EventLog {
+                contract: log_contract,
+                log_pos,
+                fields,
+                event: PhantomData,
+            });
+        }
+        Ok(events)
+    }
+
+    /// Same as `events`, but returns the first log only and fails if there is none.
+    pub fn event<E: SolEvent>(
+        &self,
+        contract: Option<Address>,
+    ) -> Result<EventLog<'_, E>, EventError> {
+        self.events::<E>(contract)?
+            .into_iter()
+            .next()
+            .ok_or(EventError::EventNotFound {
+                event: E::SIGNATURE,
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloy_sol_types::{sol, sol_data, SolEvent};
+    use crypto_bigint::U256;
+
+    use super::{EventError, EventField};
+    use crate::input_types::receipt::{LogFieldData, ReceiptData};
+
+    sol! {
+        event Swap(address indexed sender, address indexed recipient, int256 amount0, int256 amount1, uint160 sqrtPriceX96, uint128 liquidity, int24 tick);
+        event Transfer(address indexed from, address indexed to, uint256 value);
+    }
+
+    const POOL: [u8; 20] = [0x88; 20];
+
+    fn field(log_pos: u32, is_topic: bool, field_index: u32, value: U256) -> LogFieldData {
+        LogFieldData {
+            contract: POOL,
+            topic: Swap::SIGNATURE_HASH.0,
+            log_pos,
+            is_topic,
+            field_index,
+            value,
+        }
+    }
+
+    fn receipt(fields: Vec<LogFieldData>) -> ReceiptData {
+        ReceiptData::add_receipt([1; 32], 21756846, U256::from_u32(7), 1738475315, 1, fields)
+    }
+
+    #[test]
+    fn test_swap_event() {
+        let recipient =
+            U256::from_be_hex("0000000000000000000000006a000f20005980200259b80c5102003040001068");
+        let receipt = receipt(vec![
+            field(17, false, 1, U256::from_u32(100)),
+            field(17, true, 2, recipient),
+            field(17, false, 0, U256::MAX),
+            field(18, false, 1, U256::from_u32(200)),
+        ]);
+
+        let swaps = receipt.events::<Swap>(Some(POOL)).unwrap();
+        assert_eq!(swaps.len(), 2);
+        assert_eq!(swaps[0].log_pos, 17);
+        assert_eq!(swaps[0].value(EventField::Data(1)), Ok(U256::from_u32(100)));
+        assert_eq!(swaps[0].value(EventField::Topic(2)), Ok(recipient));
+        let swap_recipient = swaps[0]
+            .get::<sol_data::Address>(EventField::Topic(2))
+            .unwrap();
+        assert_eq!(swap_recipient.as_slice(), &recipient.to_be_bytes()[12..]);
+        assert_eq!(
+            swaps[1].value(EventField::Topic(2)),
+            Err(EventError::MissingField {
+                log_pos: 18,
+                field: EventField::Topic(2)
+            })
+        );
+        assert!(matches!(
+            swaps[0].value(EventField::Topic(3)),
+            Err(EventError::TopicOutOfRange { index: 3, .. })
+        ));
+        assert!(matches!(
+            swaps[0].get::<sol_data::Address>(EventField::Data(0)),
+            Err(EventError::InvalidValue {
+                sol_type: "address",
+                ..
+            })
+        ));
+        assert!(matches!(
+            receipt.event::<Transfer>(None),
+            Err(EventError::EventNotFound { .. })
+        ));
+        assert!(matches!(
+            receipt.event::<Swap>(Some([0; 20])),
+            Err(EventError::ContractMismatch { log_pos: 17, .. })
+        ));
+    }
+
+    #[test]
+    fn test_inconsistent_log() {
+        let mut other = field(17, true, 2, U256::ZERO);
+        other.contract = [0; 20];
+        let receipt = receipt(vec![field(17, false, 1, U256::ZERO), other]);
+        assert_eq!(
+            receipt.events::<Swap>(None).err(),
+            Some(EventError::InconsistentLog { log_pos: 17 })
+        );
+    }
+}
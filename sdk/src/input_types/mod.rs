@@ -4,6 +4,9 @@ use storage::StorageSlotData;
 use transaction::TransactionData;
 
 
+pub mod account;
+pub mod block_header;
+pub mod event;
 pub mod receipt;
 pub mod storage;
 pub mod transaction;
@@ -2,12 +2,10 @@ use crypto_bigint::U256;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    commitment::{commit, CommitmentVersion, InputType},
     data_types::{
-        address::Address,
-        byte32::Bytes32,
-        hash_out::{MerkleInput, HASH_OUT},
+        address::Address, byte32::Bytes32, field_elements::ToFieldElements, hash_out::MerkleInput,
     },
-    poseidon2_hash::Poseidon2,
 };
 
 use super::utils::{address_to_u32_vec, topic_to_u32_vec, u256_to_u32_vec};
@@ -25,6 +23,8 @@ pub struct ReceiptData {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogFieldData {
     pub contract: Address,
+    /// The event signature, `topics[0]` of the log. `V0` commitments only bind its first
+    /// 6 bytes, `V1` commitments bind all of it.
     pub topic: Bytes32,
     pub log_pos: u32,
     pub is_topic: bool,
@@ -52,6 +52,19 @@ impl ReceiptData {
     }
 
     pub fn commit_inputs(&self) -> MerkleInput {
+        self.commit_inputs_with(CommitmentVersion::V0)
+    }
+
+    pub fn commit_inputs_with(&self, version: CommitmentVersion) -> MerkleInput {
+        let elements = match version {
+            CommitmentVersion::V0 => self.v0_elements(),
+            CommitmentVersion::V1 => self.to_field_elements(),
+        };
+        commit(InputType::Receipt, version, &elements)
+    }
+
+    /// The elements committed by `V0`, as proven by the gateway.
+    fn v0_elements(&self) -> Vec<u32> {
         let mut inputs: Vec<u32> = vec![];
         inputs.push(self.block_num);
         let base_fee_elements = u256_to_u32_vec(self.block_base_fee);
@@ -76,7 +89,29 @@ impl ReceiptData {
             inputs.extend(value_elements);
         }
 
-        Poseidon2::<HASH_OUT>::hash(inputs.as_slice())
+        inputs
+    }
+}
+
+impl ToFieldElements for ReceiptData {
+    fn write_field_elements(&self, elements: &mut Vec<u32>) {
+        self.transaction_hash.write_field_elements(elements);
+        self.block_num.write_field_elements(elements);
+        self.block_base_fee.write_field_elements(elements);
+        self.block_time.write_field_elements(elements);
+        self.mpt_key_path.write_field_elements(elements);
+        self.fields.write_field_elements(elements);
+    }
+}
+
+impl ToFieldElements for LogFieldData {
+    fn write_field_elements(&self, elements: &mut Vec<u32>) {
+        self.contract.write_field_elements(elements);
+        self.topic.write_field_elements(elements);
+        self.log_pos.write_field_elements(elements);
+        self.is_topic.write_field_elements(elements);
+        self.field_index.write_field_elements(elements);
+        self.value.write_field_elements(elements);
     }
 }
 
@@ -85,7 +120,8 @@ mod test {
     use crypto_bigint::U256;
 
     use crate::{
-        data_types::{address::Address, byte32::Bytes32},
+        commitment::{commit, CommitmentVersion, InputType},
+        data_types::{address::Address, byte32::Bytes32, field_elements::ToFieldElements},
         input_types::receipt::{LogFieldData, ReceiptData},
         Hex,
     };
@@ -133,4 +169,60 @@ mod test {
 
         println!("receipt: {:?}", receipt);
     }
+
+    #[test]
+    fn test_v1_binds_every_field() {
+        let field = LogFieldData {
+            contract: [1; 20],
+            topic: [2; 32],
+            log_pos: 0,
+            is_topic: false,
+            field_index: 0,
+            value: U256::from_u32(3),
+        };
+        let receipt =
+            ReceiptData::add_receipt([4; 32], 21135753, U256::ZERO, 1732158058, 5, vec![field]);
+        assert_eq!(
+            receipt.commit_inputs_with(CommitmentVersion::V1),
+            commit(InputType::Receipt, CommitmentVersion::V1, &receipt.to_field_elements())
+        );
+
+        // neither the transaction hash nor the topic past its 6th byte is bound by V0
+        let mut other = receipt.clone();
+        other.transaction_hash = [5; 32];
+        other.fields[0].topic[31] = 0;
+        assert_eq!(other.commit_inputs(), receipt.commit_inputs());
+        assert_ne!(
+            other.commit_inputs_with(CommitmentVersion::V1),
+            receipt.commit_inputs_with(CommitmentVersion::V1)
+        );
+    }
+
+    #[test]
+    fn test_v1_binds_full_topic() {
+        // Transfer(address,address,uint256) and a topic sharing its first 6 bytes
+        let transfer = Bytes32::from_hex(
+            "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+        )
+        .unwrap();
+        let mut forged = [0; 32];
+        forged[..6].copy_from_slice(&transfer[..6]);
+        let receipt = |topic: Bytes32| {
+            let field = LogFieldData {
+                contract: [1; 20],
+                topic,
+                log_pos: 0,
+                is_topic: false,
+                field_index: 0,
+                value: U256::from_u32(3),
+            };
+            ReceiptData::add_receipt([4; 32], 21135753, U256::ZERO, 1732158058, 5, vec![field])
+        };
+
+        assert_eq!(receipt(transfer).commit_inputs(), receipt(forged).commit_inputs());
+        assert_ne!(
+            receipt(transfer).commit_inputs_with(CommitmentVersion::V1),
+            receipt(forged).commit_inputs_with(CommitmentVersion::V1)
+        );
+    }
 }
@@ -2,12 +2,10 @@ use crypto_bigint::U256;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    commitment::{commit, CommitmentVersion, InputType},
     data_types::{
-        address::Address,
-        byte32::Bytes32,
-        hash_out::{MerkleInput, HASH_OUT},
+        address::Address, byte32::Bytes32, field_elements::ToFieldElements, hash_out::MerkleInput,
     },
-    poseidon2_hash::Poseidon2,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +31,19 @@ impl StorageSlotData {
     }
 
     pub fn commit_inputs(&self) -> MerkleInput {
+        self.commit_inputs_with(CommitmentVersion::V0)
+    }
+
+    pub fn commit_inputs_with(&self, version: CommitmentVersion) -> MerkleInput {
+        let elements = match version {
+            CommitmentVersion::V0 => self.v0_elements(),
+            CommitmentVersion::V1 => self.to_field_elements(),
+        };
+        commit(InputType::StorageSlot, version, &elements)
+    }
+
+    /// The elements committed by `V0`, as proven by the gateway.
+    fn v0_elements(&self) -> Vec<u32> {
         let mut inputs: Vec<u32> = vec![];
         inputs.push(self.block_num);
         let base_fee_le = self.block_base_fee.to_be_bytes();
@@ -64,6 +75,118 @@ impl StorageSlotData {
             .collect::<Vec<_>>();
         inputs.extend(value_elements);
 
-        Poseidon2::<HASH_OUT>::hash(inputs.as_slice())
+        inputs
+    }
+}
+
+impl ToFieldElements for StorageSlotData {
+    fn write_field_elements(&self, elements: &mut Vec<u32>) {
+        self.block_num.write_field_elements(elements);
+        self.block_base_fee.write_field_elements(elements);
+        self.block_time.write_field_elements(elements);
+        self.address.write_field_elements(elements);
+        self.slot.write_field_elements(elements);
+        self.value.write_field_elements(elements);
+    }
+}
+
+/// Storage slots of one account at one block. Each slot is committed as its own
+/// `StorageSlotData` leaf, but the slots are requested in a single storage query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountStorageData {
+    pub block_num: u32,
+    pub block_base_fee: U256,
+    pub block_time: u32,
+    pub address: Address,
+    /// (slot, value) pairs, in leaf order
+    pub slots: Vec<(Bytes32, U256)>,
+}
+
+impl AccountStorageData {
+    pub fn add_account_storage(block_num: u32, block_base_fee: U256, block_time: u32, address: Address, slots: Vec<(Bytes32, U256)>) -> Self {
+        AccountStorageData {
+            block_num,
+            block_base_fee,
+            block_time,
+            address,
+            slots,
+        }
+    }
+
+    pub fn add_slot(&mut self, slot: Bytes32, value: U256) {
+        self.slots.push((slot, value));
+    }
+
+    /// One `StorageSlotData` per slot, in order.
+    pub fn storage_slots(&self) -> Vec<StorageSlotData> {
+        self.slots
+            .iter()
+            .map(|(slot, value)| {
+                StorageSlotData::add_storage_slot(
+                    self.block_num,
+                    self.block_base_fee,
+                    self.block_time,
+                    self.address,
+                    *slot,
+                    *value,
+                )
+            })
+            .collect()
+    }
+
+    /// Groups consecutive slots of the same account and block, keeping the slot order.
+    pub fn group_storage_slots(storage_slots: &[StorageSlotData]) -> Vec<AccountStorageData> {
+        let mut groups: Vec<AccountStorageData> = vec![];
+        for d in storage_slots {
+            match groups.last_mut() {
+                Some(group)
+                    if group.block_num == d.block_num
+                        && group.block_base_fee == d.block_base_fee
+                        && group.block_time == d.block_time
+                        && group.address == d.address =>
+                {
+                    group.add_slot(d.slot, d.value)
+                }
+                _ => groups.push(AccountStorageData::add_account_storage(
+                    d.block_num,
+                    d.block_base_fee,
+                    d.block_time,
+                    d.address,
+                    vec![(d.slot, d.value)],
+                )),
+            }
+        }
+        groups
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crypto_bigint::U256;
+
+    use super::{AccountStorageData, StorageSlotData};
+
+    #[test]
+    fn test_account_storage_slots() {
+        let account = AccountStorageData::add_account_storage(
+            21135753,
+            U256::from_u32(7),
+            1732158058,
+            [1; 20],
+            vec![([2; 32], U256::from_u32(3)), ([4; 32], U256::from_u32(5))],
+        );
+        let slots = account.storage_slots();
+        assert_eq!(slots.len(), 2);
+        assert_eq!(
+            slots[1].commit_inputs(),
+            StorageSlotData::add_storage_slot(21135753, U256::from_u32(7), 1732158058, [1; 20], [4; 32], U256::from_u32(5)).commit_inputs()
+        );
+
+        let mut other = slots[0].clone();
+        other.address = [9; 20];
+        let mixed = vec![slots[0].clone(), slots[1].clone(), other, slots[0].clone()];
+        let groups = AccountStorageData::group_storage_slots(&mixed);
+        assert_eq!(groups.iter().map(|g| g.slots.len()).collect::<Vec<_>>(), vec![2, 1, 1]);
+        assert_eq!(groups[0].slots, account.slots);
     }
 }
@@ -2,11 +2,10 @@ use crypto_bigint::U256;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    commitment::{commit, CommitmentVersion, InputType},
     data_types::{
-        byte32::Bytes32,
-        hash_out::{MerkleInput, HASH_OUT},
+        address::Address, byte32::Bytes32, field_elements::ToFieldElements, hash_out::MerkleInput,
     },
-    poseidon2_hash::Poseidon2,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +16,32 @@ pub struct TransactionData {
     pub block_time: u32,
     pub mpt_key_path: u32,
     pub leaf_hash: Bytes32,
+    // The decoded fields below are only bound by `V1` commitments, which the gateway can't
+    // prove yet, so they are read through the `unverified_*` accessors.
+    #[serde(default)]
+    pub(crate) from: Address,
+    #[serde(default)]
+    pub(crate) to: Option<Address>,
+    #[serde(default)]
+    pub(crate) value: U256,
+    #[serde(default)]
+    pub(crate) nonce: u64,
+    #[serde(default)]
+    pub(crate) gas_price: U256,
+    #[serde(default)]
+    pub(crate) gas_tip_cap: U256,
+    #[serde(default)]
+    pub(crate) selector: [u8; 4],
+    #[serde(default)]
+    pub(crate) calldata_fields: Vec<CalldataFieldData>,
+}
+
+/// A 32-byte word of the calldata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalldataFieldData {
+    /// Index of the word after the selector, the first argument is 0
+    pub word_index: u32,
+    pub value: U256,
 }
 
 impl TransactionData {
@@ -35,10 +60,105 @@ impl TransactionData {
             block_time,
             mpt_key_path,
             leaf_hash,
+            from: [0; 20],
+            to: None,
+            value: U256::ZERO,
+            nonce: 0,
+            gas_price: U256::ZERO,
+            gas_tip_cap: U256::ZERO,
+            selector: [0; 4],
+            calldata_fields: vec![],
         }
     }
 
+    pub fn with_fields(
+        mut self,
+        from: Address,
+        to: Option<Address>,
+        value: U256,
+        nonce: u64,
+        gas_price: U256,
+        gas_tip_cap: U256,
+    ) -> Self {
+        self.from = from;
+        self.to = to;
+        self.value = value;
+        self.nonce = nonce;
+        self.gas_price = gas_price;
+        self.gas_tip_cap = gas_tip_cap;
+        self
+    }
+
+    pub fn with_calldata(
+        mut self,
+        selector: [u8; 4],
+        calldata_fields: Vec<CalldataFieldData>,
+    ) -> Self {
+        self.selector = selector;
+        self.calldata_fields = calldata_fields;
+        self
+    }
+
+    /// The sender. Like the other `unverified_*` fields, it is only bound by `V1`
+    /// commitments, so the gateway doesn't prove it yet.
+    pub fn unverified_from(&self) -> Address {
+        self.from
+    }
+
+    /// The recipient, `None` for a contract creation.
+    pub fn unverified_to(&self) -> Option<Address> {
+        self.to
+    }
+
+    pub fn unverified_value(&self) -> U256 {
+        self.value
+    }
+
+    pub fn unverified_nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    /// Gas price of a legacy transaction, max fee per gas otherwise.
+    pub fn unverified_gas_price(&self) -> U256 {
+        self.gas_price
+    }
+
+    /// Max priority fee per gas, zero for a legacy transaction.
+    pub fn unverified_gas_tip_cap(&self) -> U256 {
+        self.gas_tip_cap
+    }
+
+    pub fn unverified_selector(&self) -> [u8; 4] {
+        self.selector
+    }
+
+    pub fn unverified_calldata_fields(&self) -> &[CalldataFieldData] {
+        &self.calldata_fields
+    }
+
+    /// The value of the calldata word at `word_index`, if it is one of the calldata fields.
+    pub fn unverified_calldata_word(&self, word_index: u32) -> Option<U256> {
+        self.calldata_fields
+            .iter()
+            .find(|field| field.word_index == word_index)
+            .map(|field| field.value)
+    }
+
     pub fn commit_inputs(&self) -> MerkleInput {
+        self.commit_inputs_with(CommitmentVersion::V0)
+    }
+
+    pub fn commit_inputs_with(&self, version: CommitmentVersion) -> MerkleInput {
+        let elements = match version {
+            CommitmentVersion::V0 => self.v0_elements(),
+            CommitmentVersion::V1 => self.to_field_elements(),
+        };
+        commit(InputType::Transaction, version, &elements)
+    }
+
+    /// The elements committed by `V0`, as proven by the gateway. The decoded fields, from
+    /// `from` on, are only committed by `V1`.
+    fn v0_elements(&self) -> Vec<u32> {
         let mut inputs: Vec<u32> = vec![];
 
         let hash_elements = self
@@ -63,6 +183,97 @@ impl TransactionData {
             .map(|chunk| (chunk[0] as u32) * 256 + chunk[1] as u32)
             .collect::<Vec<_>>();
         inputs.extend(leaf_hash_elements);
-        Poseidon2::<HASH_OUT>::hash(inputs.as_slice())
+        inputs
+    }
+}
+
+impl ToFieldElements for TransactionData {
+    fn write_field_elements(&self, elements: &mut Vec<u32>) {
+        self.hash.write_field_elements(elements);
+        self.block_num.write_field_elements(elements);
+        self.block_base_fee.write_field_elements(elements);
+        self.block_time.write_field_elements(elements);
+        self.mpt_key_path.write_field_elements(elements);
+        self.leaf_hash.write_field_elements(elements);
+        self.from.write_field_elements(elements);
+        self.to.write_field_elements(elements);
+        self.value.write_field_elements(elements);
+        self.nonce.write_field_elements(elements);
+        self.gas_price.write_field_elements(elements);
+        self.gas_tip_cap.write_field_elements(elements);
+        self.selector.write_field_elements(elements);
+        self.calldata_fields.write_field_elements(elements);
+    }
+}
+
+impl ToFieldElements for CalldataFieldData {
+    fn write_field_elements(&self, elements: &mut Vec<u32>) {
+        self.word_index.write_field_elements(elements);
+        self.value.write_field_elements(elements);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crypto_bigint::U256;
+
+    use super::{CalldataFieldData, TransactionData};
+    use crate::commitment::CommitmentVersion;
+
+    #[test]
+    fn test_transaction_fields() {
+        let tx = TransactionData::add_transaction(
+            [1; 32],
+            21135753,
+            U256::from_u32(7),
+            1732158058,
+            3,
+            [2; 32],
+        );
+        let transfer = tx
+            .clone()
+            .with_fields(
+                [3; 20],
+                Some([4; 20]),
+                U256::ZERO,
+                5,
+                U256::from_u32(8),
+                U256::from_u32(1),
+            )
+            .with_calldata(
+                [0xa9, 0x05, 0x9c, 0xbb],
+                vec![CalldataFieldData {
+                    word_index: 1,
+                    value: U256::from_u32(100),
+                }],
+            );
+        assert_eq!(
+            transfer.unverified_calldata_word(1),
+            Some(U256::from_u32(100))
+        );
+        assert_eq!(transfer.unverified_calldata_word(0), None);
+        // V0 keeps the layout proven by the gateway
+        assert_eq!(transfer.commit_inputs(), tx.commit_inputs());
+        assert_ne!(
+            transfer.commit_inputs_with(CommitmentVersion::V1),
+            tx.commit_inputs_with(CommitmentVersion::V1)
+        );
+
+        let mut other = transfer.clone();
+        other.calldata_fields[0].value = U256::from_u32(101);
+        assert_ne!(
+            transfer.commit_inputs_with(CommitmentVersion::V1),
+            other.commit_inputs_with(CommitmentVersion::V1)
+        );
+
+        // a contract creation doesn't commit like a transfer to the zero address
+        let mut creation = transfer.clone();
+        creation.to = None;
+        let mut to_zero = transfer.clone();
+        to_zero.to = Some([0; 20]);
+        assert_ne!(
+            creation.commit_inputs_with(CommitmentVersion::V1),
+            to_zero.commit_inputs_with(CommitmentVersion::V1)
+        );
     }
 }
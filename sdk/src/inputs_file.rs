@@ -0,0 +1,309 @@
+use std::{
+    fs,
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use alloy_primitives::keccak256;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{commitment::CommitmentVersion, data_types::hash_out::MerkleInput, sdk::SDK};
+
+/// Version of the file written by `SDK::save_inputs`. Files without one hold the bare `SDK`
+/// and are read as version 0.
+pub const INPUTS_FORMAT_VERSION: u32 = 1;
+
+/// File name used by `SDK::save_inputs` in the given directory.
+pub const INPUTS_FILE_NAME: &str = "request_prove_inputs.json";
+
+#[derive(Debug, Error)]
+pub enum InputsFileError {
+    #[error("failed to access {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid inputs file: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("unsupported inputs format version {0}, expected at most {INPUTS_FORMAT_VERSION}")]
+    UnsupportedFormat(u32),
+    #[error("{field} of the header is {header}, but {inputs} in the inputs")]
+    HeaderMismatch {
+        field: &'static str,
+        header: String,
+        inputs: String,
+    },
+    #[error("checksum mismatch: the header has {header}, the inputs hash to {computed}")]
+    ChecksumMismatch { header: String, computed: String },
+    #[error("input commitment mismatch: saved {saved:?}, recomputed {recomputed:?}")]
+    CommitmentMismatch {
+        saved: MerkleInput,
+        recomputed: MerkleInput,
+    },
+}
+
+/// The size of each section of the input commitment tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputLimits {
+    pub max_receipt_size: u32,
+    pub max_storage_size: u32,
+    pub max_tx_size: u32,
+    pub max_block_header_size: u32,
+    pub max_account_size: u32,
+}
+
+impl InputLimits {
+    fn of(sdk: &SDK) -> Self {
+        InputLimits {
+            max_receipt_size: sdk.max_receipt_size,
+            max_storage_size: sdk.max_storage_size,
+            max_tx_size: sdk.max_tx_size,
+            max_block_header_size: sdk.max_block_header_size,
+            max_account_size: sdk.max_account_size,
+        }
+    }
+}
+
+/// The saved inputs, behind a header that repeats what they commit to. The checksum is the
+/// keccak256 of the JSON encoding of the inputs.
+#[derive(Serialize, Deserialize)]
+pub struct InputsFile {
+    pub format_version: u32,
+    pub chain_id: u64,
+    pub limits: InputLimits,
+    pub commitment_version: CommitmentVersion,
+    pub input_commitments: MerkleInput,
+    pub checksum: String,
+    pub inputs: SDK,
+}
+
+impl InputsFile {
+    /// Wraps `sdk`, recomputing its input commitment.
+    pub fn new(sdk: SDK) -> Result<Self, InputsFileError> {
+        Ok(InputsFile {
+            format_version: INPUTS_FORMAT_VERSION,
+            chain_id: sdk.chain_id,
+            limits: InputLimits::of(&sdk),
+            commitment_version: sdk.commitment_version,
+            input_commitments: sdk.merkle_tree().root(),
+            checksum: checksum(&sdk)?,
+            inputs: sdk,
+        })
+    }
+
+    /// Reads the file at `path` without verifying it, see `verify`.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, InputsFileError> {
+        let path = path.as_ref();
+        let file = fs::File::open(path).map_err(|source| InputsFileError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let value: Value = serde_json::from_reader(BufReader::new(file))?;
+        match value.get("format_version") {
+            // saved before the header was added: the header is derived from the inputs
+            None => {
+                let sdk: SDK = serde_json::from_value(value)?;
+                let mut file = InputsFile::new(sdk)?;
+                file.format_version = 0;
+                file.input_commitments = file.inputs.input_commitments;
+                Ok(file)
+            }
+            Some(version) => {
+                let version = serde_json::from_value(version.clone())?;
+                if version > INPUTS_FORMAT_VERSION {
+                    return Err(InputsFileError::UnsupportedFormat(version));
+                }
+                Ok(serde_json::from_value(value)?)
+            }
+        }
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), InputsFileError> {
+        let path = path.as_ref();
+        let io_error = |source| InputsFileError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let file = fs::File::create(path).map_err(io_error)?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.flush().map_err(io_error)
+    }
+
+    /// Checks the header against the inputs and recomputes the input commitment, returning
+    /// the inputs if they all agree.
+    pub fn verify(self) -> Result<SDK, InputsFileError> {
+        self.check()?;
+        Ok(self.inputs)
+    }
+
+    /// Same as `verify`, without taking the inputs.
+    pub fn check(&self) -> Result<(), InputsFileError> {
+        let sdk = &self.inputs;
+        check_header("chain id", self.chain_id, sdk.chain_id)?;
+        check_header("limits", self.limits, InputLimits::of(sdk))?;
+        check_header(
+            "commitment version",
+            self.commitment_version,
+            sdk.commitment_version,
+        )?;
+        check_header(
+            "input commitment",
+            self.input_commitments,
+            sdk.input_commitments,
+        )?;
+        let computed = checksum(sdk)?;
+        if computed != self.checksum {
+            return Err(InputsFileError::ChecksumMismatch {
+                header: self.checksum.clone(),
+                computed,
+            });
+        }
+
+        let recomputed = sdk.merkle_tree().root();
+        if recomputed != sdk.input_commitments {
+            return Err(InputsFileError::CommitmentMismatch {
+                saved: sdk.input_commitments,
+                recomputed,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn checksum(sdk: &SDK) -> Result<String, InputsFileError> {
+    Ok(format!(
+        "0x{}",
+        hex::encode(keccak256(serde_json::to_vec(sdk)?))
+    ))
+}
+
+fn check_header<T: PartialEq + std::fmt::Debug>(
+    field: &'static str,
+    header: T,
+    inputs: T,
+) -> Result<(), InputsFileError> {
+    if header != inputs {
+        return Err(InputsFileError::HeaderMismatch {
+            field,
+            header: format!("{:?}", header),
+            inputs: format!("{:?}", inputs),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crypto_bigint::U256;
+
+    use super::{InputsFile, InputsFileError, INPUTS_FORMAT_VERSION};
+    use crate::{
+        input_types::receipt::ReceiptData,
+        sdk::{Builder, SDK},
+    };
+
+    fn test_sdk() -> SDK {
+        let receipt =
+            ReceiptData::add_receipt([1; 32], 21135753, U256::from_u32(7), 1732158058, 1, vec![]);
+        Builder::new()
+            .with_chain_id(1)
+            .with_receipts(vec![receipt])
+            .init(32, 0, 0)
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("inputs-file-{}-{}.json", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let path = temp_path("round-trip");
+        let sdk = test_sdk();
+        sdk.save_inputs_as(&path).unwrap();
+
+        let file = InputsFile::read(&path).unwrap();
+        assert_eq!(file.format_version, INPUTS_FORMAT_VERSION);
+        assert_eq!(file.chain_id, 1);
+        assert_eq!(file.limits.max_receipt_size, 32);
+        assert_eq!(file.input_commitments, sdk.input_commitments);
+
+        let loaded = SDK::load_inputs(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.input_commitments, sdk.input_commitments);
+        assert_eq!(loaded.receipts.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_tampered_inputs_are_rejected() {
+        let mut file = InputsFile::new(test_sdk()).unwrap();
+        file.chain_id = 10;
+        assert!(matches!(
+            file.verify(),
+            Err(InputsFileError::HeaderMismatch {
+                field: "chain id",
+                ..
+            })
+        ));
+
+        let mut file = InputsFile::new(test_sdk()).unwrap();
+        file.inputs.receipts.as_mut().unwrap()[0].block_num += 1;
+        assert!(matches!(
+            file.verify(),
+            Err(InputsFileError::ChecksumMismatch { .. })
+        ));
+
+        // a consistent checksum does not hide a commitment that doesn't match the inputs
+        let mut sdk = test_sdk();
+        sdk.receipts.as_mut().unwrap()[0].block_num += 1;
+        let saved = sdk.input_commitments;
+        let file = InputsFile {
+            input_commitments: saved,
+            ..InputsFile::new(sdk).unwrap()
+        };
+        assert!(matches!(
+            file.verify(),
+            Err(InputsFileError::CommitmentMismatch { saved: s, .. }) if s == saved
+        ));
+    }
+
+    #[test]
+    fn test_stale_inputs_are_not_saved() {
+        let path = temp_path("stale");
+        let mut sdk = test_sdk();
+        sdk.receipts.as_mut().unwrap()[0].block_num += 1;
+        assert!(matches!(
+            sdk.save_inputs_as(&path),
+            Err(InputsFileError::HeaderMismatch {
+                field: "input commitment",
+                ..
+            })
+        ));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_legacy_and_future_files() {
+        let path = temp_path("legacy");
+        let sdk = test_sdk();
+        std::fs::write(&path, serde_json::to_vec(&sdk).unwrap()).unwrap();
+        let file = InputsFile::read(&path).unwrap();
+        assert_eq!(file.format_version, 0);
+        assert_eq!(
+            file.verify().unwrap().input_commitments,
+            sdk.input_commitments
+        );
+
+        std::fs::write(&path, r#"{"format_version": 2}"#).unwrap();
+        let err = SDK::load_inputs(&path).err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(err, Some(InputsFileError::UnsupportedFormat(2))));
+
+        assert!(matches!(
+            SDK::load_inputs(&path),
+            Err(InputsFileError::Io { .. })
+        ));
+    }
+}
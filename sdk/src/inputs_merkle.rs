@@ -1,3 +1,5 @@
+use thiserror::Error;
+
 use crate::{
     data_types::hash_out::{MerkleInput, HASH_OUT},
     poseidon2_hash::Poseidon2,
@@ -10,7 +12,51 @@ pub struct MerkleNode {
     right: Option<Box<MerkleInput>>,
 }
 
+/// Leaf used to pad a tree up to the next power of two.
+pub const PADDING_LEAF: MerkleInput = [0; HASH_OUT];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum MerkleError {
+    #[error("merkle tree requires at least one leaf")]
+    EmptyLeaves,
+    #[error("merkle tree leaf count {0} is not a power of two")]
+    NotPowerOfTwo(usize),
+}
+
+/// Builds the tree over `leaves`, padding with `PADDING_LEAF` up to the next power of two.
+/// Panics if `leaves` is empty.
 pub fn build_merkle_tree(leaves: &[MerkleInput]) -> MerkleNode {
+    if leaves.is_empty() {
+        panic!("{}", MerkleError::EmptyLeaves);
+    }
+    build_nodes(&pad_leaves(leaves))
+}
+
+/// Builds the tree over `leaves` without padding, rejecting an empty slice or a leaf
+/// count that is not a power of two.
+pub fn try_build_merkle_tree(leaves: &[MerkleInput]) -> Result<MerkleNode, MerkleError> {
+    check_leaf_count(leaves.len())?;
+    Ok(build_nodes(leaves))
+}
+
+/// Returns `leaves` padded with `PADDING_LEAF` up to the next power of two.
+pub fn pad_leaves(leaves: &[MerkleInput]) -> Vec<MerkleInput> {
+    let mut padded = leaves.to_vec();
+    padded.resize(leaves.len().next_power_of_two(), PADDING_LEAF);
+    padded
+}
+
+pub fn check_leaf_count(count: usize) -> Result<(), MerkleError> {
+    if count == 0 {
+        return Err(MerkleError::EmptyLeaves);
+    }
+    if !count.is_power_of_two() {
+        return Err(MerkleError::NotPowerOfTwo(count));
+    }
+    Ok(())
+}
+
+fn build_nodes(leaves: &[MerkleInput]) -> MerkleNode {
     let mut nodes: Vec<MerkleNode> = leaves
         .iter()
         .map(|leaf| MerkleNode {
@@ -25,10 +71,7 @@ pub fn build_merkle_tree(leaves: &[MerkleInput]) -> MerkleNode {
             let left = nodes[2 * i].clone();
             let right = nodes[2 * i + 1].clone();
 
-            let mut inputs = Vec::with_capacity(left.hash.len() + right.hash.len());
-            inputs.extend(left.hash);
-            inputs.extend(right.hash);
-            let combined_hash = Poseidon2::<HASH_OUT>::hash(&inputs);
+            let combined_hash = hash_nodes(&left.hash, &right.hash);
             new_nodes.push(MerkleNode {
                 hash: combined_hash,
                 left: Some(Box::new(left.hash)),
@@ -39,3 +82,170 @@ pub fn build_merkle_tree(leaves: &[MerkleInput]) -> MerkleNode {
     }
     nodes[0].clone()
 }
+
+/// A merkle tree over the input commitments which keeps every level,
+/// so that inclusion proofs can be produced for any leaf.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleTree {
+    /// `levels[0]` holds the leaves and the last level holds the root.
+    levels: Vec<Vec<MerkleInput>>,
+}
+
+impl MerkleTree {
+    /// Builds the tree with the same node hashing and padding as `build_merkle_tree`.
+    /// Panics if `leaves` is empty.
+    pub fn new(leaves: &[MerkleInput]) -> Self {
+        if leaves.is_empty() {
+            panic!("{}", MerkleError::EmptyLeaves);
+        }
+        Self::from_padded(pad_leaves(leaves))
+    }
+
+    /// Builds the tree without padding, see `try_build_merkle_tree`.
+    pub fn try_new(leaves: &[MerkleInput]) -> Result<Self, MerkleError> {
+        check_leaf_count(leaves.len())?;
+        Ok(Self::from_padded(leaves.to_vec()))
+    }
+
+    fn from_padded(leaves: Vec<MerkleInput>) -> Self {
+        let mut levels = vec![leaves];
+        while levels[levels.len() - 1].len() > 1 {
+            let nodes = &levels[levels.len() - 1];
+            let parents = nodes
+                .chunks_exact(2)
+                .map(|pair| hash_nodes(&pair[0], &pair[1]))
+                .collect();
+            levels.push(parents);
+        }
+        MerkleTree { levels }
+    }
+
+    pub fn root(&self) -> MerkleInput {
+        self.levels[self.levels.len() - 1][0]
+    }
+
+    /// The leaves of the tree, including any padding leaves.
+    pub fn leaves(&self) -> &[MerkleInput] {
+        &self.levels[0]
+    }
+
+    /// Number of levels above the leaves, i.e. the length of every proof.
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// Returns the sibling hashes from the leaf up to the root, or `None` if the
+    /// leaf index is out of range.
+    pub fn proof(&self, leaf_index: usize) -> Option<Vec<MerkleInput>> {
+        if leaf_index >= self.leaves().len() {
+            return None;
+        }
+        let mut index = leaf_index;
+        let mut path = Vec::with_capacity(self.depth());
+        for level in &self.levels[..self.depth()] {
+            path.push(*level.get(index ^ 1)?);
+            index /= 2;
+        }
+        Some(path)
+    }
+}
+
+/// Checks that `leaf` sits at `index` in the tree with the given `root`, where `path`
+/// holds the sibling hashes from the leaf level upwards as returned by `MerkleTree::proof`.
+pub fn verify_proof(
+    root: &MerkleInput,
+    leaf: &MerkleInput,
+    index: usize,
+    path: &[MerkleInput],
+) -> bool {
+    if path.len() < usize::BITS as usize && index >> path.len() != 0 {
+        return false;
+    }
+    let mut index = index;
+    let mut hash = *leaf;
+    for sibling in path {
+        hash = if index & 1 == 0 {
+            hash_nodes(&hash, sibling)
+        } else {
+            hash_nodes(sibling, &hash)
+        };
+        index /= 2;
+    }
+    hash == *root
+}
+
+fn hash_nodes(left: &MerkleInput, right: &MerkleInput) -> MerkleInput {
+    let mut inputs = Vec::with_capacity(left.len() + right.len());
+    inputs.extend(left);
+    inputs.extend(right);
+    Poseidon2::<HASH_OUT>::hash(&inputs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        build_merkle_tree, try_build_merkle_tree, verify_proof, MerkleError, MerkleTree,
+        PADDING_LEAF,
+    };
+    use crate::data_types::hash_out::MerkleInput;
+
+    fn test_leaves(n: u32) -> Vec<MerkleInput> {
+        (0..n).map(|i| [i; 8]).collect()
+    }
+
+    #[test]
+    fn test_root_matches_build_merkle_tree() {
+        let leaves = test_leaves(8);
+        let tree = MerkleTree::new(&leaves);
+        assert_eq!(tree.root(), build_merkle_tree(&leaves).hash);
+        assert_eq!(tree.depth(), 3);
+    }
+
+    #[test]
+    fn test_proofs_verify() {
+        let leaves = test_leaves(8);
+        let tree = MerkleTree::new(&leaves);
+        let root = tree.root();
+        for (i, leaf) in leaves.iter().enumerate() {
+            let path = tree.proof(i).unwrap();
+            assert!(verify_proof(&root, leaf, i, &path));
+            assert!(!verify_proof(&root, leaf, i ^ 1, &path));
+            assert!(!verify_proof(&root, &[99; 8], i, &path));
+        }
+        assert!(tree.proof(8).is_none());
+    }
+
+    #[test]
+    fn test_index_out_of_range_rejected() {
+        let leaves = test_leaves(4);
+        let tree = MerkleTree::new(&leaves);
+        let path = tree.proof(1).unwrap();
+        assert!(!verify_proof(&tree.root(), &leaves[1], 1 + 4, &path));
+    }
+
+    #[test]
+    fn test_odd_leaves_are_padded() {
+        let leaves = test_leaves(5);
+        let mut padded = leaves.clone();
+        padded.extend([PADDING_LEAF; 3]);
+        assert_eq!(build_merkle_tree(&leaves), build_merkle_tree(&padded));
+
+        let tree = MerkleTree::new(&leaves);
+        assert_eq!(tree.leaves().len(), 8);
+        assert_eq!(tree.root(), build_merkle_tree(&padded).hash);
+        let path = tree.proof(4).unwrap();
+        assert!(verify_proof(&tree.root(), &leaves[4], 4, &path));
+    }
+
+    #[test]
+    fn test_strict_build_rejects_bad_counts() {
+        assert_eq!(try_build_merkle_tree(&[]), Err(MerkleError::EmptyLeaves));
+        assert_eq!(
+            try_build_merkle_tree(&test_leaves(6)),
+            Err(MerkleError::NotPowerOfTwo(6))
+        );
+        assert_eq!(MerkleTree::try_new(&test_leaves(3)), Err(MerkleError::NotPowerOfTwo(3)));
+        let leaves = test_leaves(4);
+        assert_eq!(try_build_merkle_tree(&leaves).unwrap(), build_merkle_tree(&leaves));
+    }
+}
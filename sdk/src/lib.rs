@@ -1,13 +1,27 @@
+pub mod commitment;
 pub mod data_types;
+pub mod dummy;
+pub mod error;
+#[cfg(feature = "fetch")]
+pub mod fetch;
 pub mod input_types;
+#[cfg(not(target_os = "zkvm"))]
+pub mod inputs_file;
 pub mod inputs_merkle;
+#[cfg(not(target_os = "zkvm"))]
+pub mod mpt;
+pub mod output;
 pub mod poseidon2_hash;
+#[cfg(not(target_os = "zkvm"))]
+mod poseidon2_constants;
+pub mod query;
 pub mod sdk;
 
 use alloy_sol_types::sol;
 
 sol! {
     /// The public values encoded as a struct that can be easily deserialized inside Solidity.
+    /// It precedes the output struct of the program, see `output::encode_output`.
     struct PublicValuesStruct {
         uint32[8] input_commitment;
     }
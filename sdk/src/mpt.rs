@@ -0,0 +1,1200 @@
+use alloy_primitives::keccak256;
+use crypto_bigint::U256;
+use thiserror::Error;
+
+use crate::{
+    data_types::{address::Address, byte32::Bytes32},
+    input_types::{
+        account::AccountData, block_header::BlockHeaderData, receipt::ReceiptData,
+        storage::StorageSlotData, transaction::TransactionData,
+    },
+};
+
+/// Root of an empty trie, `keccak256(rlp(""))`
+pub const EMPTY_ROOT: Bytes32 = [
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+];
+
+/// Code hash of an account without code, `keccak256("")`
+pub const EMPTY_CODE_HASH: Bytes32 = [
+    0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7, 0x03, 0xc0,
+    0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04, 0x5d, 0x85, 0xa4, 0x70,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MptError {
+    #[error("invalid RLP: {0}")]
+    InvalidRlp(&'static str),
+    #[error("proof node {index} does not match the hash of its parent")]
+    NodeHashMismatch { index: usize },
+    #[error("proof node {index} is not a trie node")]
+    InvalidNode { index: usize },
+    #[error("proof ends before the key is resolved")]
+    IncompleteProof,
+    #[error("key is not in the trie")]
+    KeyNotFound,
+    #[error("{field} does not match the block header")]
+    HeaderMismatch { field: &'static str },
+    #[error("{field} does not match the proven leaf")]
+    LeafMismatch { field: &'static str },
+    #[error("field {field_index} of log {log_pos} does not match the proven receipt")]
+    LogFieldMismatch { log_pos: u32, field_index: u32 },
+}
+
+/// The header fields the inputs are checked against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub hash: Bytes32,
+    pub parent_hash: Bytes32,
+    pub number: u64,
+    pub timestamp: u64,
+    /// Zero before London
+    pub base_fee: U256,
+    pub state_root: Bytes32,
+    pub transactions_root: Bytes32,
+    pub receipts_root: Bytes32,
+}
+
+impl BlockHeader {
+    /// Decodes an RLP-encoded header. Its hash is the hash of `rlp`, so a header from an
+    /// untrusted source should be checked against a known block hash.
+    pub fn decode(rlp: &[u8]) -> Result<Self, MptError> {
+        let fields = RlpItem::decode(rlp)?.list()?;
+        if fields.len() < 15 {
+            return Err(MptError::InvalidRlp("block header"));
+        }
+        Ok(BlockHeader {
+            hash: keccak256(rlp).0,
+            parent_hash: fields[0].as_bytes32()?,
+            number: fields[8].as_u64()?,
+            timestamp: fields[11].as_u64()?,
+            base_fee: fields.get(15).map_or(Ok(U256::ZERO), RlpItem::as_u256)?,
+            state_root: fields[3].as_bytes32()?,
+            transactions_root: fields[4].as_bytes32()?,
+            receipts_root: fields[5].as_bytes32()?,
+        })
+    }
+
+    fn check_block(
+        &self,
+        block_num: u32,
+        block_base_fee: U256,
+        block_time: u32,
+    ) -> Result<(), MptError> {
+        if self.number != block_num as u64 {
+            return Err(MptError::HeaderMismatch { field: "block_num" });
+        }
+        if self.base_fee != block_base_fee {
+            return Err(MptError::HeaderMismatch {
+                field: "block_base_fee",
+            });
+        }
+        if self.timestamp != block_time as u64 {
+            return Err(MptError::HeaderMismatch {
+                field: "block_time",
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks a block header input against the decoded header.
+    pub fn verify_block_header(&self, block_header: &BlockHeaderData) -> Result<(), MptError> {
+        self.check_block(
+            block_header.block_num,
+            block_header.block_base_fee,
+            block_header.block_time,
+        )?;
+        if self.hash != block_header.hash {
+            return Err(MptError::HeaderMismatch { field: "hash" });
+        }
+        if self.parent_hash != block_header.parent_hash {
+            return Err(MptError::HeaderMismatch {
+                field: "parent_hash",
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks the account with the `accountProof` of `eth_getProof`. An account that is not in
+    /// the state is proven as an empty account.
+    pub fn verify_account(
+        &self,
+        account: &AccountData,
+        account_proof: &[Vec<u8>],
+    ) -> Result<(), MptError> {
+        if self.number != account.block_num as u64 {
+            return Err(MptError::HeaderMismatch { field: "block_num" });
+        }
+        let (nonce, balance, storage_root, code_hash) =
+            match self.account(account.address, account_proof)? {
+                Some(fields) => fields,
+                None => (0, U256::ZERO, EMPTY_ROOT, EMPTY_CODE_HASH),
+            };
+        let checks = [
+            ("nonce", nonce == account.nonce),
+            ("balance", balance == account.balance),
+            ("storage_root", storage_root == account.storage_root),
+            ("code_hash", code_hash == account.code_hash),
+        ];
+        if let Some((field, _)) = checks.iter().find(|(_, ok)| !ok) {
+            return Err(MptError::LeafMismatch { field });
+        }
+        Ok(())
+    }
+
+    /// The nonce, balance, storage root and code hash of the account at `address`.
+    fn account(
+        &self,
+        address: Address,
+        account_proof: &[Vec<u8>],
+    ) -> Result<Option<(u64, U256, Bytes32, Bytes32)>, MptError> {
+        let Some(account) = verify_proof(self.state_root, &keccak256(address).0, account_proof)?
+        else {
+            return Ok(None);
+        };
+        // [nonce, balance, storage root, code hash]
+        let fields = RlpItem::decode(&account)?.list()?;
+        if fields.len() != 4 {
+            return Err(MptError::InvalidRlp("account"));
+        }
+        Ok(Some((
+            fields[0].as_u64()?,
+            fields[1].as_u256()?,
+            fields[2].as_bytes32()?,
+            fields[3].as_bytes32()?,
+        )))
+    }
+
+    /// Checks that the receipt at `mpt_key_path` is in the receipts trie and holds every log
+    /// field of `receipt`, `log_pos` being the position of the log in the receipt.
+    pub fn verify_receipt(&self, receipt: &ReceiptData, proof: &[Vec<u8>]) -> Result<(), MptError> {
+        self.check_block(
+            receipt.block_num,
+            receipt.block_base_fee,
+            receipt.block_time,
+        )?;
+        let leaf = verify_proof(
+            self.receipts_root,
+            &encode_index(receipt.mpt_key_path),
+            proof,
+        )?
+        .ok_or(MptError::KeyNotFound)?;
+        // [status, cumulative gas used, bloom, logs]
+        let fields = RlpItem::decode(strip_tx_type(&leaf))?.list()?;
+        let logs = fields
+            .get(3)
+            .ok_or(MptError::InvalidRlp("receipt"))?
+            .list()?;
+        for field in &receipt.fields {
+            let mismatch = MptError::LogFieldMismatch {
+                log_pos: field.log_pos,
+                field_index: field.field_index,
+            };
+            // [address, topics, data]
+            let log = logs
+                .get(field.log_pos as usize)
+                .ok_or(mismatch.clone())?
+                .list()?;
+            if log.len() != 3 {
+                return Err(MptError::InvalidRlp("log"));
+            }
+            let topics = log[1].list()?;
+            let data = log[2].bytes()?;
+            let value = if field.is_topic {
+                topics
+                    .get(field.field_index as usize)
+                    .map(RlpItem::as_bytes32)
+                    .transpose()?
+            } else {
+                let start = field.field_index as usize * 32;
+                data.get(start..start + 32)
+                    .map(|word| word.try_into().unwrap())
+            };
+            let topic0 = topics.first().map(RlpItem::as_bytes32).transpose()?;
+            if log[0].as_address()? != field.contract
+                || topic0 != Some(field.topic)
+                || value.map(|value| U256::from_be_slice(&value)) != Some(field.value)
+            {
+                return Err(mismatch);
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the transaction at `mpt_key_path` is in the transactions trie, hashes to
+    /// `hash` and carries the fields of `tx`. `leaf_hash` is the hash of the leaf node, the last
+    /// proof node. `from` is not checked, as it is recovered from the signature.
+    pub fn verify_transaction(
+        &self,
+        tx: &TransactionData,
+        proof: &[Vec<u8>],
+    ) -> Result<(), MptError> {
+        self.check_block(tx.block_num, tx.block_base_fee, tx.block_time)?;
+        let leaf = verify_proof(
+            self.transactions_root,
+            &encode_index(tx.mpt_key_path),
+            proof,
+        )?
+        .ok_or(MptError::KeyNotFound)?;
+        if keccak256(&leaf).0 != tx.hash {
+            return Err(MptError::LeafMismatch { field: "hash" });
+        }
+        if proof.last().map(|node| keccak256(node).0) != Some(tx.leaf_hash) {
+            return Err(MptError::LeafMismatch { field: "leaf_hash" });
+        }
+
+        let fields = RlpItem::decode(strip_tx_type(&leaf))?.list()?;
+        // legacy: [nonce, gas price, gas, to, value, input, ..]
+        // access list: [chain id, nonce, gas price, gas, to, value, input, ..]
+        // later types: [chain id, nonce, max priority fee, max fee, gas, to, value, input, ..]
+        let (nonce, gas_price, gas_tip_cap, to) = match leaf.first() {
+            Some(0x01) => (1, 2, None, 4),
+            Some(tx_type) if *tx_type <= 0x7f => (1, 3, Some(2), 5),
+            _ => (0, 1, None, 3),
+        };
+        if fields.len() < to + 3 {
+            return Err(MptError::InvalidRlp("transaction"));
+        }
+        let tx_to = match fields[to].bytes()? {
+            [] => None,
+            _ => Some(fields[to].as_address()?),
+        };
+        let input = fields[to + 2].bytes()?;
+        let selector: Option<[u8; 4]> = input.get(..4).map(|selector| selector.try_into().unwrap());
+        let checks = [
+            ("nonce", fields[nonce].as_u64()? == tx.nonce),
+            ("to", tx_to == tx.to),
+            ("value", fields[to + 1].as_u256()? == tx.value),
+            ("gas_price", fields[gas_price].as_u256()? == tx.gas_price),
+            (
+                "gas_tip_cap",
+                gas_tip_cap.map_or(Ok(U256::ZERO), |i| fields[i].as_u256())? == tx.gas_tip_cap,
+            ),
+            ("selector", selector.unwrap_or_default() == tx.selector),
+        ];
+        if let Some((field, _)) = checks.iter().find(|(_, ok)| !ok) {
+            return Err(MptError::LeafMismatch { field });
+        }
+        for field in &tx.calldata_fields {
+            let start = 4 + field.word_index as usize * 32;
+            if input.get(start..start + 32).map(U256::from_be_slice) != Some(field.value) {
+                return Err(MptError::LeafMismatch {
+                    field: "calldata_fields",
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks a slot with the `accountProof` and the `storageProof` of `eth_getProof`. A zero
+    /// value may be proven by the absence of the slot.
+    pub fn verify_storage_slot(
+        &self,
+        slot: &StorageSlotData,
+        account_proof: &[Vec<u8>],
+        storage_proof: &[Vec<u8>],
+    ) -> Result<(), MptError> {
+        self.check_block(slot.block_num, slot.block_base_fee, slot.block_time)?;
+        let (_, _, storage_root, _) = self
+            .account(slot.address, account_proof)?
+            .ok_or(MptError::KeyNotFound)?;
+        let value = match verify_proof(storage_root, &keccak256(slot.slot).0, storage_proof)? {
+            Some(value) => RlpItem::decode(&value)?.as_u256()?,
+            None => U256::ZERO,
+        };
+        if value != slot.value {
+            return Err(MptError::LeafMismatch { field: "value" });
+        }
+        Ok(())
+    }
+}
+
+/// Walks `proof` from `root` along `key` and returns the value of the key, or `None` if the
+/// proof shows the key is not in the trie. Nodes shorter than 32 bytes are embedded in their
+/// parent and are not expected in `proof`.
+pub fn verify_proof(
+    root: Bytes32,
+    key: &[u8],
+    proof: &[Vec<u8>],
+) -> Result<Option<Vec<u8>>, MptError> {
+    if root == EMPTY_ROOT && proof.is_empty() {
+        return Ok(None);
+    }
+    let nibbles = key
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect::<Vec<_>>();
+    let mut key_pos = 0;
+    let mut hash = root;
+    for (index, node) in proof.iter().enumerate() {
+        if keccak256(node).0 != hash {
+            return Err(MptError::NodeHashMismatch { index });
+        }
+        let mut node = RlpItem::decode(node)?;
+        // follow the nodes embedded in this one until a child is referenced by its hash
+        loop {
+            let items = node.list().map_err(|_| MptError::InvalidNode { index })?;
+            let child = match items.len() {
+                17 if key_pos == nibbles.len() => {
+                    let value = items[16].bytes()?;
+                    return Ok((!value.is_empty()).then(|| value.to_vec()));
+                }
+                17 => {
+                    key_pos += 1;
+                    items[nibbles[key_pos - 1] as usize]
+                }
+                2 => {
+                    let (is_leaf, path) =
+                        decode_path(items[0].bytes()?).ok_or(MptError::InvalidNode { index })?;
+                    let rest = &nibbles[key_pos..];
+                    if is_leaf {
+                        if rest != path.as_slice() {
+                            return Ok(None);
+                        }
+                        return Ok(Some(items[1].bytes()?.to_vec()));
+                    }
+                    if !rest.starts_with(&path) {
+                        return Ok(None);
+                    }
+                    key_pos += path.len();
+                    items[1]
+                }
+                _ => return Err(MptError::InvalidNode { index }),
+            };
+            if child.is_list {
+                node = child;
+                continue;
+            }
+            match child.payload {
+                [] => return Ok(None),
+                payload => {
+                    hash = payload
+                        .try_into()
+                        .map_err(|_| MptError::InvalidNode { index })?;
+                    break;
+                }
+            }
+        }
+    }
+    Err(MptError::IncompleteProof)
+}
+
+/// Builds the trie of a block's transactions or receipts, keyed by their index, and returns
+/// its root and the proof of the one at `index`, for nodes that only return the raw items.
+pub fn index_trie_proof(values: &[Vec<u8>], index: u32) -> (Bytes32, Vec<Vec<u8>>) {
+    let leaves = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| (encode_index(i as u32), value.clone()))
+        .collect::<Vec<_>>();
+    trie_proof(&leaves, &encode_index(index))
+}
+
+/// Builds the trie of `leaves` and returns its root and the proof of `key`, in the order
+/// `verify_proof` walks it. The proof of a missing key ends where the key leaves the trie.
+pub fn trie_proof(leaves: &[(Vec<u8>, Vec<u8>)], key: &[u8]) -> (Bytes32, Vec<Vec<u8>>) {
+    if leaves.is_empty() {
+        return (EMPTY_ROOT, vec![]);
+    }
+    let mut leaves = leaves
+        .iter()
+        .map(|(key, value)| (to_nibbles(key), value.as_slice()))
+        .collect::<Vec<_>>();
+    leaves.sort();
+    let mut proof = vec![];
+    let root = encode_node(&leaves, 0, Some(&to_nibbles(key)), &mut proof);
+    // the root is hashed even when it is shorter than 32 bytes
+    if proof.last() != Some(&root) {
+        proof.push(root.clone());
+    }
+    proof.reverse();
+    (keccak256(&root).0, proof)
+}
+
+/// Encodes the node holding `leaves`, sorted and sharing their first `depth` nibbles, and
+/// collects the hashed nodes on the path of `key`, deepest first.
+fn encode_node(
+    leaves: &[(Vec<u8>, &[u8])],
+    depth: usize,
+    key: Option<&[u8]>,
+    proof: &mut Vec<Vec<u8>>,
+) -> Vec<u8> {
+    let node = if let [(path, value)] = leaves {
+        let path = &path[depth..];
+        rlp_list(&[rlp_bytes(&encode_path(true, path)), rlp_bytes(value)])
+    } else {
+        let (first, last) = (&leaves[0].0, &leaves[leaves.len() - 1].0);
+        let shared = first[depth..]
+            .iter()
+            .zip(&last[depth..])
+            .take_while(|(a, b)| a == b)
+            .count();
+        if shared > 0 {
+            let path = &first[depth..depth + shared];
+            let key = key.filter(|key| key.get(depth..depth + shared) == Some(path));
+            let child = encode_node(leaves, depth + shared, key, proof);
+            rlp_list(&[rlp_bytes(&encode_path(false, path)), node_ref(child)])
+        } else {
+            let mut items = vec![];
+            let mut rest = leaves;
+            // sorted, so a key ending here comes first
+            let value = match rest.first() {
+                Some((path, value)) if path.len() == depth => {
+                    rest = &rest[1..];
+                    rlp_bytes(value)
+                }
+                _ => rlp_bytes(&[]),
+            };
+            for nibble in 0..16 {
+                let len = rest
+                    .iter()
+                    .take_while(|(path, _)| path[depth] == nibble)
+                    .count();
+                let (children, next) = rest.split_at(len);
+                rest = next;
+                if children.is_empty() {
+                    items.push(rlp_bytes(&[]));
+                    continue;
+                }
+                let key = key.filter(|key| key.get(depth) == Some(&nibble));
+                items.push(node_ref(encode_node(children, depth + 1, key, proof)));
+            }
+            items.push(value);
+            rlp_list(&items)
+        }
+    };
+    if key.is_some() && node.len() >= 32 {
+        proof.push(node.clone());
+    }
+    node
+}
+
+/// A child is embedded in its parent when shorter than 32 bytes, referenced by hash otherwise.
+fn node_ref(node: Vec<u8>) -> Vec<u8> {
+    if node.len() < 32 {
+        return node;
+    }
+    rlp_bytes(&keccak256(&node).0)
+}
+
+fn to_nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+/// The hex-prefix encoding of the path of a leaf or an extension node.
+fn encode_path(is_leaf: bool, nibbles: &[u8]) -> Vec<u8> {
+    let flag = if is_leaf { 0x20 } else { 0 };
+    let (mut path, rest) = match nibbles.len() % 2 {
+        1 => (vec![flag | 0x10 | nibbles[0]], &nibbles[1..]),
+        _ => (vec![flag], nibbles),
+    };
+    path.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    path
+}
+
+/// Decodes the hex-prefix encoded path of a leaf or an extension node.
+fn decode_path(path: &[u8]) -> Option<(bool, Vec<u8>)> {
+    let (first, rest) = path.split_first()?;
+    let is_leaf = match first >> 4 {
+        0 | 1 => false,
+        2 | 3 => true,
+        _ => return None,
+    };
+    let mut nibbles = vec![];
+    if first & 0x10 != 0 {
+        nibbles.push(first & 0x0f);
+    }
+    nibbles.extend(rest.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]));
+    Some((is_leaf, nibbles))
+}
+
+/// The key of a receipt or a transaction, `rlp(index)`
+fn encode_index(index: u32) -> Vec<u8> {
+    match index {
+        0 => vec![0x80],
+        1..0x80 => vec![index as u8],
+        _ => {
+            let bytes = index.to_be_bytes();
+            let bytes = &bytes[index.leading_zeros() as usize / 8..];
+            [&[0x80 + bytes.len() as u8], bytes].concat()
+        }
+    }
+}
+
+/// Skips the type of a typed transaction or receipt.
+fn strip_tx_type(leaf: &[u8]) -> &[u8] {
+    match leaf.first() {
+        Some(tx_type) if *tx_type <= 0x7f => &leaf[1..],
+        _ => leaf,
+    }
+}
+
+fn rlp_prefix(base: u8, len: usize) -> Vec<u8> {
+    if len < 56 {
+        return vec![base + len as u8];
+    }
+    let len_bytes = len.to_be_bytes();
+    let len_bytes = &len_bytes[len.leading_zeros() as usize / 8..];
+    [&[base + 55 + len_bytes.len() as u8], len_bytes].concat()
+}
+
+fn rlp_bytes(bytes: &[u8]) -> Vec<u8> {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        return bytes.to_vec();
+    }
+    [rlp_prefix(0x80, bytes.len()), bytes.to_vec()].concat()
+}
+
+fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload = items.concat();
+    [rlp_prefix(0xc0, payload.len()), payload].concat()
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RlpItem<'a> {
+    is_list: bool,
+    payload: &'a [u8],
+}
+
+impl<'a> RlpItem<'a> {
+    /// Decodes the item `rlp` consists of.
+    fn decode(rlp: &'a [u8]) -> Result<Self, MptError> {
+        let (item, rest) = Self::decode_first(rlp)?;
+        if !rest.is_empty() {
+            return Err(MptError::InvalidRlp("trailing bytes"));
+        }
+        Ok(item)
+    }
+
+    fn decode_first(rlp: &'a [u8]) -> Result<(Self, &'a [u8]), MptError> {
+        let (&prefix, rest) = rlp
+            .split_first()
+            .ok_or(MptError::InvalidRlp("empty input"))?;
+        let (is_list, offset, len) = match prefix {
+            0x00..=0x7f => {
+                return Ok((
+                    RlpItem {
+                        is_list: false,
+                        payload: &rlp[..1],
+                    },
+                    rest,
+                ))
+            }
+            0x80..=0xb7 => (false, 0, (prefix - 0x80) as usize),
+            0xb8..=0xbf => (false, (prefix - 0xb7) as usize, 0),
+            0xc0..=0xf7 => (true, 0, (prefix - 0xc0) as usize),
+            0xf8..=0xff => (true, (prefix - 0xf7) as usize, 0),
+        };
+        let len = if offset == 0 {
+            len
+        } else {
+            let len_bytes = rest
+                .get(..offset)
+                .ok_or(MptError::InvalidRlp("truncated length"))?;
+            len_bytes
+                .iter()
+                .try_fold(0usize, |len, byte| {
+                    len.checked_mul(256).map(|len| len + *byte as usize)
+                })
+                .ok_or(MptError::InvalidRlp("length overflow"))?
+        };
+        let rest = &rest[offset..];
+        if rest.len() < len {
+            return Err(MptError::InvalidRlp("truncated payload"));
+        }
+        let (payload, rest) = rest.split_at(len);
+        Ok((RlpItem { is_list, payload }, rest))
+    }
+
+    fn list(&self) -> Result<Vec<RlpItem<'a>>, MptError> {
+        if !self.is_list {
+            return Err(MptError::InvalidRlp("expected a list"));
+        }
+        let mut items = vec![];
+        let mut rest = self.payload;
+        while !rest.is_empty() {
+            let (item, next) = Self::decode_first(rest)?;
+            items.push(item);
+            rest = next;
+        }
+        Ok(items)
+    }
+
+    fn bytes(&self) -> Result<&'a [u8], MptError> {
+        if self.is_list {
+            return Err(MptError::InvalidRlp("expected a string"));
+        }
+        Ok(self.payload)
+    }
+
+    fn as_u256(&self) -> Result<U256, MptError> {
+        let bytes = self.bytes()?;
+        if bytes.len() > 32 {
+            return Err(MptError::InvalidRlp("integer overflow"));
+        }
+        let mut word = [0; 32];
+        word[32 - bytes.len()..].copy_from_slice(bytes);
+        Ok(U256::from_be_slice(&word))
+    }
+
+    fn as_u64(&self) -> Result<u64, MptError> {
+        let bytes = self.bytes()?;
+        if bytes.len() > 8 {
+            return Err(MptError::InvalidRlp("integer overflow"));
+        }
+        Ok(bytes.iter().fold(0, |n, byte| (n << 8) | *byte as u64))
+    }
+
+    fn as_bytes32(&self) -> Result<Bytes32, MptError> {
+        self.bytes()?
+            .try_into()
+            .map_err(|_| MptError::InvalidRlp("expected 32 bytes"))
+    }
+
+    fn as_address(&self) -> Result<Address, MptError> {
+        self.bytes()?
+            .try_into()
+            .map_err(|_| MptError::InvalidRlp("expected 20 bytes"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloy_primitives::keccak256;
+    use crypto_bigint::U256;
+
+    use super::{
+        encode_index, index_trie_proof, rlp_bytes, rlp_list, strip_tx_type, trie_proof,
+        verify_proof, BlockHeader, MptError, RlpItem, EMPTY_CODE_HASH, EMPTY_ROOT,
+    };
+    use crate::input_types::{
+        account::AccountData,
+        block_header::BlockHeaderData,
+        receipt::{LogFieldData, ReceiptData},
+        storage::StorageSlotData,
+        transaction::{CalldataFieldData, TransactionData},
+    };
+
+    const BLOCK: u32 = 21756846;
+    const BASE_FEE: u64 = 0x1e2cc31f9;
+    const TIME: u32 = 1738466355;
+
+    fn rlp_uint(n: u64) -> Vec<u8> {
+        let bytes = n.to_be_bytes();
+        rlp_bytes(&bytes[n.leading_zeros() as usize / 8..])
+    }
+
+    /// A trie of one leaf holding `value` at `key`, returning its root and its proof.
+    fn single_leaf(key: &[u8], value: &[u8]) -> (super::Bytes32, Vec<Vec<u8>>) {
+        let leaf = rlp_list(&[rlp_bytes(&[&[0x20], key].concat()), rlp_bytes(value)]);
+        (keccak256(&leaf).0, vec![leaf])
+    }
+
+    fn header(
+        state_root: [u8; 32],
+        transactions_root: [u8; 32],
+        receipts_root: [u8; 32],
+    ) -> BlockHeader {
+        let rlp = rlp_list(&[
+            rlp_bytes(&[1; 32]),
+            rlp_bytes(&[2; 32]),
+            rlp_bytes(&[3; 20]),
+            rlp_bytes(&state_root),
+            rlp_bytes(&transactions_root),
+            rlp_bytes(&receipts_root),
+            rlp_bytes(&[0; 256]),
+            rlp_uint(0),
+            rlp_uint(BLOCK as u64),
+            rlp_uint(30_000_000),
+            rlp_uint(12_000_000),
+            rlp_uint(TIME as u64),
+            rlp_bytes(b"extra"),
+            rlp_bytes(&[4; 32]),
+            rlp_bytes(&[0; 8]),
+            rlp_uint(BASE_FEE),
+        ]);
+        let header = BlockHeader::decode(&rlp).unwrap();
+        assert_eq!(header.hash, keccak256(&rlp).0);
+        header
+    }
+
+    #[test]
+    fn test_embedded_nodes() {
+        // keys 0x10 and 0x20 hang off the root branch as embedded leaves
+        let leaf = rlp_list(&[rlp_bytes(&[0x30]), rlp_bytes(&[5])]);
+        let mut children = vec![rlp_bytes(&[]); 17];
+        children[1] = leaf.clone();
+        children[2] = leaf;
+        let branch = rlp_list(&children);
+        let root = keccak256(&branch).0;
+        let proof = vec![branch];
+
+        assert_eq!(verify_proof(root, &[0x10], &proof), Ok(Some(vec![5])));
+        assert_eq!(verify_proof(root, &[0x30], &proof), Ok(None));
+        assert_eq!(verify_proof(root, &[0x11], &proof), Ok(None));
+        assert_eq!(keccak256([0x80]).0, EMPTY_ROOT);
+        assert_eq!(verify_proof(EMPTY_ROOT, &[0x10], &[]), Ok(None));
+        assert_eq!(
+            verify_proof([0; 32], &[0x10], &proof),
+            Err(MptError::NodeHashMismatch { index: 0 })
+        );
+    }
+
+    #[test]
+    fn test_trie_proof() {
+        // "dogs" and "puppy" of the ethereum/tests trie tests, with extension nodes and values
+        // in branches
+        let leaves = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.as_bytes().to_vec(), value.as_bytes().to_vec()))
+                .collect::<Vec<_>>()
+        };
+        let dogs = leaves(&[
+            ("doe", "reindeer"),
+            ("dog", "puppy"),
+            ("dogglesworth", "cat"),
+        ]);
+        let puppy = leaves(&[
+            ("do", "verb"),
+            ("horse", "stallion"),
+            ("doge", "coin"),
+            ("dog", "puppy"),
+        ]);
+        for (leaves, root) in [
+            (
+                dogs,
+                "8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3",
+            ),
+            (
+                puppy,
+                "5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84",
+            ),
+        ] {
+            let root: [u8; 32] = hex::decode(root).unwrap().try_into().unwrap();
+            for (key, value) in &leaves {
+                let (proven_root, proof) = trie_proof(&leaves, key);
+                assert_eq!(proven_root, root);
+                assert_eq!(verify_proof(root, key, &proof), Ok(Some(value.clone())));
+            }
+            for missing in ["d", "dogg", "cat", "horses"] {
+                let (_, proof) = trie_proof(&leaves, missing.as_bytes());
+                assert_eq!(verify_proof(root, missing.as_bytes(), &proof), Ok(None));
+            }
+        }
+        assert_eq!(trie_proof(&[], b"dog"), (EMPTY_ROOT, vec![]));
+
+        // 200 transactions put the keys 0x81.. under the branch of 0x80
+        let txs = (0..200u32)
+            .map(|i| [i.to_be_bytes().as_slice(), &[0xee; 40]].concat())
+            .collect::<Vec<_>>();
+        for index in [0, 1, 127, 128, 199] {
+            let (root, proof) = index_trie_proof(&txs, index);
+            assert_eq!(
+                verify_proof(root, &encode_index(index), &proof),
+                Ok(Some(txs[index as usize].clone()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_block_header() {
+        // the mainnet genesis block
+        let rlp = rlp_list(&[
+            rlp_bytes(&[0; 32]),
+            rlp_bytes(
+                &hex::decode("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347")
+                    .unwrap(),
+            ),
+            rlp_bytes(&[0; 20]),
+            rlp_bytes(
+                &hex::decode("d7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544")
+                    .unwrap(),
+            ),
+            rlp_bytes(&EMPTY_ROOT),
+            rlp_bytes(&EMPTY_ROOT),
+            rlp_bytes(&[0; 256]),
+            rlp_uint(0x400000000),
+            rlp_uint(0),
+            rlp_uint(5000),
+            rlp_uint(0),
+            rlp_uint(0),
+            rlp_bytes(
+                &hex::decode("11bbe8db4e347b4e8c937c1c8370e4b5ed33adb3db69cbdb7a38e1e50b1b82fa")
+                    .unwrap(),
+            ),
+            rlp_bytes(&[0; 32]),
+            rlp_bytes(&[0, 0, 0, 0, 0, 0, 0, 0x42]),
+        ]);
+        let header = BlockHeader::decode(&rlp).unwrap();
+        let hash: [u8; 32] =
+            hex::decode("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        assert_eq!(header.hash, hash);
+
+        let block_header = BlockHeaderData::add_block_header(0, U256::ZERO, 0, hash, [0; 32]);
+        assert_eq!(header.verify_block_header(&block_header), Ok(()));
+        let mut wrong_parent = block_header.clone();
+        wrong_parent.parent_hash = [1; 32];
+        assert_eq!(
+            header.verify_block_header(&wrong_parent),
+            Err(MptError::HeaderMismatch {
+                field: "parent_hash"
+            })
+        );
+        let mut wrong_hash = block_header;
+        wrong_hash.hash[0] ^= 1;
+        assert_eq!(
+            header.verify_block_header(&wrong_hash),
+            Err(MptError::HeaderMismatch { field: "hash" })
+        );
+    }
+
+    #[test]
+    fn test_verify_account() {
+        let account_rlp = |nonce: u64, balance: u64| {
+            rlp_list(&[
+                rlp_uint(nonce),
+                rlp_uint(balance),
+                rlp_bytes(&EMPTY_ROOT),
+                rlp_bytes(&EMPTY_CODE_HASH),
+            ])
+        };
+        let accounts = (0..100u8)
+            .map(|i| {
+                (
+                    keccak256([i; 20]).0.to_vec(),
+                    account_rlp(i as u64, 1000 + i as u64),
+                )
+            })
+            .collect::<Vec<_>>();
+        let (state_root, proof) = trie_proof(&accounts, &keccak256([42; 20]).0);
+        let header = header(state_root, [0; 32], [0; 32]);
+
+        let account = AccountData::add_account(
+            [42; 20],
+            BLOCK,
+            U256::from_u32(1042),
+            42,
+            EMPTY_CODE_HASH,
+            EMPTY_ROOT,
+        );
+        assert_eq!(header.verify_account(&account, &proof), Ok(()));
+        let mut wrong_balance = account.clone();
+        wrong_balance.balance = U256::from_u32(1043);
+        assert_eq!(
+            header.verify_account(&wrong_balance, &proof),
+            Err(MptError::LeafMismatch { field: "balance" })
+        );
+
+        // an account missing from the state is empty
+        let (_, proof) = trie_proof(&accounts, &keccak256([200; 20]).0);
+        let mut missing =
+            AccountData::add_account([200; 20], BLOCK, U256::ZERO, 0, EMPTY_CODE_HASH, EMPTY_ROOT);
+        assert_eq!(header.verify_account(&missing, &proof), Ok(()));
+        missing.nonce = 1;
+        assert_eq!(
+            header.verify_account(&missing, &proof),
+            Err(MptError::LeafMismatch { field: "nonce" })
+        );
+    }
+
+    #[test]
+    fn test_verify_receipt() {
+        let pool = [0x88; 20];
+        let topic = [0xc4; 32];
+        let mut data = vec![0; 64];
+        data[63] = 100;
+        let log = rlp_list(&[
+            rlp_bytes(&pool),
+            rlp_list(&[rlp_bytes(&topic), rlp_bytes(&[0x6a; 32])]),
+            rlp_bytes(&data),
+        ]);
+        let receipt_rlp = |logs: Vec<Vec<u8>>| {
+            let fields = rlp_list(&[
+                rlp_uint(1),
+                rlp_uint(21000),
+                rlp_bytes(&[0; 256]),
+                rlp_list(&logs),
+            ]);
+            [vec![0x02], fields].concat()
+        };
+        // receipts 0 and 1 have keys 0x80 and 0x01, so they are the children 8 and 0 of the root
+        let leaves = [
+            rlp_list(&[rlp_bytes(&[0x30]), rlp_bytes(&receipt_rlp(vec![]))]),
+            rlp_list(&[
+                rlp_bytes(&[0x31]),
+                rlp_bytes(&receipt_rlp(vec![log.clone(), log])),
+            ]),
+        ];
+        let mut children = vec![rlp_bytes(&[]); 17];
+        children[8] = rlp_bytes(&keccak256(&leaves[0]).0);
+        children[0] = rlp_bytes(&keccak256(&leaves[1]).0);
+        let branch = rlp_list(&children);
+        let header = header([0; 32], [0; 32], keccak256(&branch).0);
+        let proof = vec![branch, leaves[1].clone()];
+
+        let field = |is_topic, field_index, value| LogFieldData {
+            contract: pool,
+            topic,
+            log_pos: 1,
+            is_topic,
+            field_index,
+            value,
+        };
+        let receipt = ReceiptData::add_receipt(
+            [1; 32],
+            BLOCK,
+            U256::from_u64(BASE_FEE),
+            TIME,
+            1,
+            vec![
+                field(false, 1, U256::from_u32(100)),
+                field(true, 1, U256::from_be_slice(&[0x6a; 32])),
+            ],
+        );
+        assert_eq!(header.verify_receipt(&receipt, &proof), Ok(()));
+
+        let mut wrong_value = receipt.clone();
+        wrong_value.fields[0].value = U256::from_u32(101);
+        assert_eq!(
+            header.verify_receipt(&wrong_value, &proof),
+            Err(MptError::LogFieldMismatch {
+                log_pos: 1,
+                field_index: 1
+            })
+        );
+        let mut wrong_index = receipt.clone();
+        wrong_index.mpt_key_path = 2;
+        assert_eq!(
+            header.verify_receipt(&wrong_index, &proof),
+            Err(MptError::KeyNotFound)
+        );
+        let mut wrong_time = receipt;
+        wrong_time.block_time += 1;
+        assert_eq!(
+            header.verify_receipt(&wrong_time, &proof),
+            Err(MptError::HeaderMismatch {
+                field: "block_time"
+            })
+        );
+    }
+
+    #[test]
+    fn test_verify_transaction() {
+        let mut input = vec![0x35, 0x93, 0x56, 0x4c];
+        input.extend([0; 63]);
+        input.push(7);
+        let tx_rlp = [
+            vec![0x02],
+            rlp_list(&[
+                rlp_uint(1),
+                rlp_uint(42),
+                rlp_uint(1_000_000_000),
+                rlp_uint(10_000_000_000),
+                rlp_uint(200_000),
+                rlp_bytes(&[0x22; 20]),
+                rlp_uint(0),
+                rlp_bytes(&input),
+                rlp_list(&[]),
+                rlp_uint(1),
+                rlp_bytes(&[5; 32]),
+                rlp_bytes(&[6; 32]),
+            ]),
+        ]
+        .concat();
+        let (root, proof) = single_leaf(&[0x80], &tx_rlp);
+        let header = header([0; 32], root, [0; 32]);
+
+        let tx = TransactionData::add_transaction(
+            keccak256(&tx_rlp).0,
+            BLOCK,
+            U256::from_u64(BASE_FEE),
+            TIME,
+            0,
+            keccak256(&proof[0]).0,
+        )
+        .with_fields(
+            [0x11; 20],
+            Some([0x22; 20]),
+            U256::ZERO,
+            42,
+            U256::from_u64(10_000_000_000),
+            U256::from_u64(1_000_000_000),
+        )
+        .with_calldata(
+            [0x35, 0x93, 0x56, 0x4c],
+            vec![CalldataFieldData {
+                word_index: 1,
+                value: U256::from_u32(7),
+            }],
+        );
+        assert_eq!(header.verify_transaction(&tx, &proof), Ok(()));
+
+        let mut wrong_tip = tx.clone();
+        wrong_tip.gas_tip_cap = U256::ZERO;
+        assert_eq!(
+            header.verify_transaction(&wrong_tip, &proof),
+            Err(MptError::LeafMismatch {
+                field: "gas_tip_cap"
+            })
+        );
+        let mut wrong_leaf_hash = tx.clone();
+        wrong_leaf_hash.leaf_hash = [0; 32];
+        assert_eq!(
+            header.verify_transaction(&wrong_leaf_hash, &proof),
+            Err(MptError::LeafMismatch { field: "leaf_hash" })
+        );
+        let mut wrong_word = tx;
+        wrong_word.calldata_fields[0].value = U256::ZERO;
+        assert_eq!(
+            header.verify_transaction(&wrong_word, &proof),
+            Err(MptError::LeafMismatch {
+                field: "calldata_fields"
+            })
+        );
+    }
+
+    #[test]
+    fn test_verify_transaction_of_max_type() {
+        // EIP-2718 types go up to 0x7f, legacy transactions start with a list prefix
+        assert_eq!(strip_tx_type(&[0x7f, 0xc0]), [0xc0]);
+        assert_eq!(strip_tx_type(&[0xc0]), [0xc0]);
+
+        let tx_rlp = [
+            vec![0x7f],
+            rlp_list(&[
+                rlp_uint(1),
+                rlp_uint(3),
+                rlp_uint(1_000_000_000),
+                rlp_uint(10_000_000_000),
+                rlp_uint(21_000),
+                rlp_bytes(&[0x22; 20]),
+                rlp_uint(5),
+                rlp_bytes(&[]),
+            ]),
+        ]
+        .concat();
+        let (root, proof) = single_leaf(&[0x80], &tx_rlp);
+        let header = header([0; 32], root, [0; 32]);
+        let tx = TransactionData::add_transaction(
+            keccak256(&tx_rlp).0,
+            BLOCK,
+            U256::from_u64(BASE_FEE),
+            TIME,
+            0,
+            keccak256(&proof[0]).0,
+        )
+        .with_fields(
+            [0x11; 20],
+            Some([0x22; 20]),
+            U256::from_u32(5),
+            3,
+            U256::from_u64(10_000_000_000),
+            U256::from_u64(1_000_000_000),
+        );
+        assert_eq!(header.verify_transaction(&tx, &proof), Ok(()));
+    }
+
+    #[test]
+    fn test_verify_storage_slot() {
+        let (address, slot) = ([0x88; 20], [0; 32]);
+        let (storage_root, storage_proof) = single_leaf(&keccak256(slot).0, &rlp_uint(0x0100));
+        let account = rlp_list(&[
+            rlp_uint(1),
+            rlp_uint(0),
+            rlp_bytes(&storage_root),
+            rlp_bytes(&[0xc5; 32]),
+        ]);
+        let (state_root, account_proof) = single_leaf(&keccak256(address).0, &account);
+        let header = header(state_root, [0; 32], [0; 32]);
+
+        let slot_data = StorageSlotData::add_storage_slot(
+            BLOCK,
+            U256::from_u64(BASE_FEE),
+            TIME,
+            address,
+            slot,
+            U256::from_u32(0x0100),
+        );
+        assert_eq!(
+            header.verify_storage_slot(&slot_data, &account_proof, &storage_proof),
+            Ok(())
+        );
+
+        // the proof of slot 0 shows slot 1 is absent, so slot 1 is zero
+        let mut other_slot = slot_data.clone();
+        other_slot.slot[31] = 1;
+        assert_eq!(
+            header.verify_storage_slot(&other_slot, &account_proof, &storage_proof),
+            Err(MptError::LeafMismatch { field: "value" })
+        );
+        other_slot.value = U256::ZERO;
+        assert_eq!(
+            header.verify_storage_slot(&other_slot, &account_proof, &storage_proof),
+            Ok(())
+        );
+        assert_eq!(
+            header.verify_storage_slot(&slot_data, &account_proof, &account_proof),
+            Err(MptError::NodeHashMismatch { index: 0 })
+        );
+    }
+
+    #[test]
+    fn test_verify_transaction_behind_extension() {
+        // in a block of 130 transactions, the keys 0x8180 and 0x8181 share the nibble 8 below
+        // the branch of 0x81, which an extension node holds
+        let txs = (0..130u64)
+            .map(|nonce| {
+                rlp_list(&[
+                    rlp_uint(nonce),
+                    rlp_uint(10_000_000_000),
+                    rlp_uint(21_000),
+                    rlp_bytes(&[0x33; 20]),
+                    rlp_uint(1),
+                    rlp_bytes(&[]),
+                    rlp_uint(37),
+                    rlp_bytes(&[5; 32]),
+                    rlp_bytes(&[6; 32]),
+                ])
+            })
+            .collect::<Vec<_>>();
+        let (root, proof) = index_trie_proof(&txs, 129);
+        let is_extension = |node: &Vec<u8>| {
+            let items = RlpItem::decode(node).unwrap().list().unwrap();
+            items.len() == 2 && items[0].bytes().unwrap()[0] >> 4 < 2
+        };
+        assert!(proof.iter().any(is_extension));
+        let header = header([0; 32], root, [0; 32]);
+
+        let tx = TransactionData::add_transaction(
+            keccak256(&txs[129]).0,
+            BLOCK,
+            U256::from_u64(BASE_FEE),
+            TIME,
+            129,
+            keccak256(proof.last().unwrap()).0,
+        )
+        .with_fields(
+            [0x11; 20],
+            Some([0x33; 20]),
+            U256::ONE,
+            129,
+            U256::from_u64(10_000_000_000),
+            U256::ZERO,
+        );
+        assert_eq!(header.verify_transaction(&tx, &proof), Ok(()));
+        // 0x8180 leaves the path of the proof below the extension, at the last branch
+        let mut wrong_index = tx;
+        wrong_index.mpt_key_path = 128;
+        assert_eq!(
+            header.verify_transaction(&wrong_index, &proof),
+            Err(MptError::NodeHashMismatch { index: 4 })
+        );
+    }
+}
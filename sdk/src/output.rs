@@ -0,0 +1,78 @@
+use alloy_sol_types::{SolStruct, SolType, SolValue};
+
+use crate::{data_types::hash_out::MerkleInput, error::SdkError, PublicValuesStruct};
+
+/// ABI-encodes the output of the program after the input commitment, the way a Solidity
+/// callback decodes it:
+///
+/// ```solidity
+/// (PublicValuesStruct memory publicValues, Output memory output) =
+///     abi.decode(data, (PublicValuesStruct, Output));
+/// ```
+pub fn encode_output<T: SolStruct + SolValue + Clone>(
+    input_commitments: &MerkleInput,
+    output: &T,
+) -> Vec<u8> {
+    let public_values = PublicValuesStruct {
+        input_commitment: *input_commitments,
+    };
+    (public_values, output.clone()).abi_encode_params()
+}
+
+/// Decodes the input commitment and the output encoded by `encode_output`.
+pub fn decode_output<T: SolStruct>(data: &[u8]) -> Result<(MerkleInput, T), SdkError> {
+    let (public_values, output) = <(PublicValuesStruct, T)>::abi_decode_params(data, true)
+        .map_err(|e| SdkError::InvalidOutput(e.to_string()))?;
+    Ok((public_values.input_commitment, output))
+}
+
+#[cfg(test)]
+mod test {
+    use alloy_primitives::U256;
+    use alloy_sol_types::sol;
+
+    use super::{decode_output, encode_output};
+    use crate::error::SdkError;
+
+    sol! {
+        #[derive(Debug, PartialEq)]
+        struct TradingVolume {
+            address user;
+            uint256 volume;
+        }
+
+        #[derive(Debug, PartialEq)]
+        struct Labels {
+            string[] names;
+        }
+    }
+
+    const COMMITMENT: [u32; 8] = [1, 2, 3, 4, 5, 6, 7, 0xffffffff];
+
+    #[test]
+    fn test_encode_output() {
+        let output = TradingVolume {
+            user: [0x6a; 20].into(),
+            volume: U256::from(1000),
+        };
+        let data = encode_output(&COMMITMENT, &output);
+        // a static struct is encoded in place: 8 commitment words, then the user and the volume
+        assert_eq!(data.len(), 10 * 32);
+        assert_eq!(data[7 * 32 + 28..8 * 32], [0xff; 4]);
+        assert_eq!(data[9 * 32 + 30..], [0x03, 0xe8]);
+        assert_eq!(decode_output(&data), Ok((COMMITMENT, output)));
+    }
+
+    #[test]
+    fn test_decode_dynamic_output() {
+        let output = Labels {
+            names: vec!["usdc".to_string(), "weth".to_string()],
+        };
+        let data = encode_output(&COMMITMENT, &output);
+        assert_eq!(decode_output(&data), Ok((COMMITMENT, output)));
+        assert!(matches!(
+            decode_output::<Labels>(&data[..data.len() - 32]),
+            Err(SdkError::InvalidOutput(_))
+        ));
+    }
+}
@@ -1,24 +1,87 @@
 #[cfg(target_os = "zkvm")]
 use crate::syscall_poseidon2_permute;
 
-/// A stateful hasher for Poseidon2.
-#[derive(Default)]
+#[cfg(not(target_os = "zkvm"))]
+use crate::poseidon2_constants::{RC_16_30, ROUNDS_F, ROUNDS_P};
+use p3_baby_bear::BabyBear;
+#[cfg(not(target_os = "zkvm"))]
+use p3_baby_bear::DiffusionMatrixBabyBear;
+use p3_field::{AbstractField, PrimeField32};
+#[cfg(not(target_os = "zkvm"))]
+use p3_poseidon2::Poseidon2ExternalMatrixGeneral;
+#[cfg(not(target_os = "zkvm"))]
+use p3_symmetric::Permutation;
+#[cfg(not(target_os = "zkvm"))]
+use std::sync::LazyLock;
+
+/// The permutation computed by `syscall_poseidon2_permute`, for use outside the zkVM.
+#[cfg(not(target_os = "zkvm"))]
+static PERMUTATION: LazyLock<
+    p3_poseidon2::Poseidon2<
+        BabyBear,
+        Poseidon2ExternalMatrixGeneral,
+        DiffusionMatrixBabyBear,
+        16,
+        7,
+    >,
+> = LazyLock::new(|| {
+    let mut external = RC_16_30
+        .map(|round| round.map(BabyBear::from_wrapped_u32))
+        .to_vec();
+    let internal = external
+        .drain(ROUNDS_F / 2..ROUNDS_F / 2 + ROUNDS_P)
+        .map(|round| round[0])
+        .collect();
+    p3_poseidon2::Poseidon2::new(
+        ROUNDS_F,
+        external,
+        Poseidon2ExternalMatrixGeneral,
+        ROUNDS_P,
+        internal,
+        DiffusionMatrixBabyBear::default(),
+    )
+});
+
+/// A stateful hasher for Poseidon2 over BabyBear.
 pub struct Poseidon2<const OUT: usize> {
-    state: [u32; 16],    // Poseidon2 works with a 16-element state.
-    buffer_count: usize, // Number of elements currently buffered.
+    state: [BabyBear; 16], // Poseidon2 works with a 16-element state.
+    buffer_count: usize,   // Number of elements currently buffered.
+}
+
+impl<const OUT: usize> Default for Poseidon2<OUT> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<const OUT: usize> Poseidon2<OUT> {
     /// Creates a new Poseidon2 hasher with an empty state.
     pub fn new() -> Self {
         Self {
-            state: [0; 16],
+            state: [BabyBear::zero(); 16],
             buffer_count: 0,
         }
     }
 
-    /// Updates the hasher state with a new input element.
+    /// Creates a hasher whose state is first permuted with the domain `tag`, `version` and the
+    /// number of elements `len` to absorb.
+    pub fn with_domain(tag: u32, version: u32, len: u32) -> Self {
+        let mut hasher = Self::new();
+        hasher.state[0] = BabyBear::from_wrapped_u32(tag);
+        hasher.state[1] = BabyBear::from_wrapped_u32(version);
+        hasher.state[2] = BabyBear::from_wrapped_u32(len);
+        hasher.permute();
+        hasher
+    }
+
+    /// Updates the hasher state with a new input element. Inputs are reduced modulo the
+    /// BabyBear prime, so values that may reach it should be split into limbs first.
     pub fn update(&mut self, input: u32) {
+        self.absorb_field(BabyBear::from_wrapped_u32(input));
+    }
+
+    /// Updates the hasher state with a new field element.
+    pub fn absorb_field(&mut self, input: BabyBear) {
         // Buffer the input directly into the state.
         self.state[self.buffer_count] += input;
         self.buffer_count += 1;
@@ -27,7 +90,6 @@ impl<const OUT: usize> Poseidon2<OUT> {
         if self.buffer_count == 15 {
             self.permute();
             self.buffer_count = 0;
-            // println!(">> permute round state: {:?}", self.state);
         }
     }
 
@@ -35,53 +97,32 @@ impl<const OUT: usize> Poseidon2<OUT> {
     pub fn finalize(mut self) -> [u32; OUT] {
         // Pad remaining elements.
         if self.buffer_count > 0 {
-            self.state[self.buffer_count] += 1; // Padding with `1`.
+            self.state[self.buffer_count] += BabyBear::one(); // Padding with `1`.
         } else {
-            self.state[0] += 1; // If empty, pad the first element.
+            self.state[0] += BabyBear::one(); // If empty, pad the first element.
         }
         self.permute(); // Apply the final permutation.
 
-        self.state[0..OUT].try_into().unwrap()
+        core::array::from_fn(|i| self.state[i].as_canonical_u32())
     }
 
     /// Computes the Poseidon2 permutation on the state.
     fn permute(&mut self) {
-        #[allow(unused_mut)]
-        let mut ret = [0_u32; 16];
-        #[cfg(target_os = "zkvm")]
-        unsafe {
-            syscall_poseidon2_permute(&self.state as *const _, &mut ret as *mut _);
-        }
-        self.state = ret;
+        self.state = permute_field(self.state);
     }
 
     /// A convenience function to hash two elements.
     pub fn hash_two(x: u32, y: u32) -> [u32; OUT] {
-        let mut state = [0_u32; 16];
-        state[0] += x;
-        state[1] += y;
-
-        #[allow(unused_mut)]
-        let mut ret = [0_u32; 16];
-        #[cfg(target_os = "zkvm")]
-        unsafe {
-            syscall_poseidon2_permute(&state as *const _, &mut ret as *mut _);
-        }
-        ret[0..OUT].try_into().unwrap()
+        let mut hasher = Self::new();
+        hasher.state[0] = BabyBear::from_wrapped_u32(x);
+        hasher.state[1] = BabyBear::from_wrapped_u32(y);
+        hasher.permute();
+        core::array::from_fn(|i| hasher.state[i].as_canonical_u32())
     }
 
     /// A convenience function to hash a single element.
     pub fn hash_single(x: u32) -> [u32; OUT] {
-        let mut state = [0_u32; 16];
-        state[0] += x;
-
-        #[allow(unused_mut)]
-        let mut ret = [0_u32; 16];
-        #[cfg(target_os = "zkvm")]
-        unsafe {
-            syscall_poseidon2_permute(&state as *const _, &mut ret as *mut _);
-        }
-        ret[0..OUT].try_into().unwrap()
+        Self::hash_two(x, 0)
     }
 
     /// A convenience function to hash multiple elements.
@@ -92,4 +133,122 @@ impl<const OUT: usize> Poseidon2<OUT> {
         }
         hasher.finalize()
     }
+
+    /// A convenience function to hash multiple field elements.
+    pub fn hash_fields(inputs: &[BabyBear]) -> [u32; OUT] {
+        let mut hasher = Poseidon2::new();
+        for &input in inputs {
+            hasher.absorb_field(input);
+        }
+        hasher.finalize()
+    }
+}
+
+/// Applies the Poseidon2 permutation through the zkVM precompile, on canonical values.
+#[cfg(target_os = "zkvm")]
+fn permute_field(state: [BabyBear; 16]) -> [BabyBear; 16] {
+    let state = state.map(|x| x.as_canonical_u32());
+    let mut ret = [0_u32; 16];
+    unsafe {
+        syscall_poseidon2_permute(&state as *const _, &mut ret as *mut _);
+    }
+    ret.map(BabyBear::from_canonical_u32)
+}
+
+/// Applies the BabyBear Poseidon2 permutation natively, for use outside the zkVM.
+#[cfg(not(target_os = "zkvm"))]
+fn permute_field(mut state: [BabyBear; 16]) -> [BabyBear; 16] {
+    PERMUTATION.permute_mut(&mut state);
+    state
+}
+
+#[cfg(all(test, not(target_os = "zkvm")))]
+mod test {
+    use p3_baby_bear::BabyBear;
+    use p3_field::{AbstractField, PrimeField32};
+
+    use super::{permute_field, Poseidon2};
+    use crate::data_types::hash_out::HASH_OUT;
+
+    fn permute_state(state: &[u32; 16]) -> [u32; 16] {
+        permute_field(state.map(BabyBear::from_canonical_u32)).map(|x| x.as_canonical_u32())
+    }
+
+    /// Vectors computed with `sp1_primitives::poseidon2_init` of `sp1-primitives` 4.1.3, the
+    /// reference the constants are taken from. They pin the host permutation but don't show it
+    /// matches `syscall_poseidon2_permute`: that takes vectors from pico-vm's Poseidon2 or a
+    /// guest run, which should replace these.
+    #[test]
+    fn test_permute_known_answers() {
+        let zeros = [0_u32; 16];
+        assert_eq!(
+            permute_state(&zeros),
+            [
+                348670919, 1568590631, 1535107508, 186917780, 587749971, 1827585060, 1218809104,
+                691692291, 1480664293, 1491566329, 366224457, 490018300, 732772134, 560796067,
+                484676252, 405025962,
+            ]
+        );
+
+        let mut counting = [0_u32; 16];
+        counting.iter_mut().enumerate().for_each(|(i, x)| *x = i as u32);
+        assert_eq!(
+            permute_state(&counting),
+            [
+                410552856, 1147418997, 535364858, 1334196174, 1968893922, 861940529, 937007407,
+                259610178, 1058452787, 534763410, 1540431395, 130847742, 1250518650, 1612262570,
+                1379296618, 1649520075,
+            ]
+        );
+
+        let mut descending = [0_u32; 16];
+        descending
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, x)| *x = BabyBear::ORDER_U32 - 1 - i as u32);
+        assert_eq!(
+            permute_state(&descending),
+            [
+                496669019, 1432200306, 410202938, 1622980245, 526553766, 1803864554, 1796456536,
+                556389717, 331779303, 780365454, 411554997, 738264450, 1243587675, 121130530,
+                1734352660, 447212387,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hash_is_not_trivial() {
+        let single = Poseidon2::<HASH_OUT>::hash_single(7);
+        let two = Poseidon2::<HASH_OUT>::hash_two(7, 0);
+        assert_eq!(single, two);
+        assert_ne!(single, [0; HASH_OUT]);
+        assert_ne!(
+            Poseidon2::<HASH_OUT>::hash(&[1, 2, 3]),
+            Poseidon2::<HASH_OUT>::hash(&[1, 2, 4])
+        );
+    }
+
+    #[test]
+    fn test_inputs_are_field_elements() {
+        let p = BabyBear::ORDER_U32;
+        assert_eq!(
+            Poseidon2::<HASH_OUT>::hash(&[p, p + 1]),
+            Poseidon2::<HASH_OUT>::hash(&[0, 1])
+        );
+        assert_eq!(
+            Poseidon2::<HASH_OUT>::hash_two(p + 7, p),
+            Poseidon2::<HASH_OUT>::hash_single(7)
+        );
+
+        // a full absorption adds each input to a permuted lane, which used to overflow u32
+        let inputs = [u32::MAX; 31];
+        let fields = inputs.map(BabyBear::from_wrapped_u32);
+        assert_eq!(
+            Poseidon2::<HASH_OUT>::hash(&inputs),
+            Poseidon2::<HASH_OUT>::hash_fields(&fields)
+        );
+        assert!(Poseidon2::<HASH_OUT>::hash(&inputs)
+            .iter()
+            .all(|x| *x < p));
+    }
 }
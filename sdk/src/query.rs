@@ -0,0 +1,619 @@
+use std::ops::RangeInclusive;
+
+use alloy_sol_types::{SolEvent, TopicList};
+use crypto_bigint::U256;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    data_types::{address::Address, byte32::Bytes32},
+    input_types::{
+        event::EventField,
+        receipt::{LogFieldData, ReceiptData},
+        storage::AccountStorageData,
+        transaction::{CalldataFieldData, TransactionData},
+    },
+};
+
+/// A log of a transaction receipt, as returned by `eth_getTransactionReceipt`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RawLog {
+    pub address: Address,
+    pub topics: Vec<Bytes32>,
+    pub data: Vec<u8>,
+    pub log_pos: u32,
+}
+
+/// A transaction receipt with the block facts a `ReceiptData` needs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RawReceipt {
+    pub transaction_hash: Bytes32,
+    pub block_num: u32,
+    pub block_base_fee: U256,
+    pub block_time: u32,
+    pub mpt_key_path: u32,
+    pub logs: Vec<RawLog>,
+}
+
+/// A transaction, as returned by `eth_getTransactionByHash`, with the block facts and the
+/// leaf hash a `TransactionData` needs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RawTransaction {
+    pub hash: Bytes32,
+    pub block_num: u32,
+    pub block_base_fee: U256,
+    pub block_time: u32,
+    pub mpt_key_path: u32,
+    pub leaf_hash: Bytes32,
+    pub from: Address,
+    /// `None` for a contract creation
+    pub to: Option<Address>,
+    pub value: U256,
+    pub nonce: u64,
+    /// Gas price of a legacy transaction, max fee per gas otherwise
+    pub gas_price: U256,
+    /// Max priority fee per gas, zero for a legacy transaction
+    pub gas_tip_cap: U256,
+    pub input: Vec<u8>,
+}
+
+impl RawTransaction {
+    /// The first 4 bytes of the input, zero if it is shorter.
+    pub fn selector(&self) -> [u8; 4] {
+        self.input
+            .get(..4)
+            .map(|selector| selector.try_into().unwrap())
+            .unwrap_or_default()
+    }
+
+    /// The `TransactionData` with the calldata words at `words`, counted after the selector.
+    pub fn transaction_data(&self, words: &[u32]) -> Result<TransactionData, QueryError> {
+        let calldata_fields = words
+            .iter()
+            .map(|word_index| {
+                let start = 4 + *word_index as usize * 32;
+                self.input
+                    .get(start..start + 32)
+                    .map(|word| CalldataFieldData {
+                        word_index: *word_index,
+                        value: U256::from_be_slice(word),
+                    })
+                    .ok_or(QueryError::CalldataOutOfRange {
+                        hash: self.hash,
+                        word_index: *word_index,
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(TransactionData::add_transaction(
+            self.hash,
+            self.block_num,
+            self.block_base_fee,
+            self.block_time,
+            self.mpt_key_path,
+            self.leaf_hash,
+        )
+        .with_fields(
+            self.from,
+            self.to,
+            self.value,
+            self.nonce,
+            self.gas_price,
+            self.gas_tip_cap,
+        )
+        .with_calldata(self.selector(), calldata_fields))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum QueryError {
+    #[error("no fields selected")]
+    NoFieldsSelected,
+    #[error("{event} has no topic {index}")]
+    TopicOutOfRange { event: &'static str, index: u32 },
+    #[error("{field:?} is out of range in log {log_pos}")]
+    FieldOutOfRange { log_pos: u32, field: EventField },
+    #[error("slot 0x{} was not read at block {block_num}", hex::encode(slot))]
+    SlotNotFound { block_num: u32, slot: Bytes32 },
+    #[error(
+        "calldata word {word_index} is out of range in transaction 0x{}",
+        hex::encode(hash)
+    )]
+    CalldataOutOfRange { hash: Bytes32, word_index: u32 },
+}
+
+/// Selects the logs of one event in raw receipts and turns them into `ReceiptData` inputs.
+/// Each matching log contributes the selected fields, in selection order, and only the
+/// receipts with at least one matching log are kept.
+///
+/// ```ignore
+/// let receipts = ReceiptQuery::event::<Swap>()
+///     .contract(pool)
+///     .topic(2, user)
+///     .blocks(21756800..=21756900)
+///     .select([EventField::Data(0), EventField::Topic(2)])
+///     .apply(&raw_receipts)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReceiptQuery {
+    event: &'static str,
+    topic0: Bytes32,
+    topic_count: usize,
+    contract: Option<Address>,
+    topics: Vec<(u32, Bytes32)>,
+    blocks: Option<RangeInclusive<u32>>,
+    fields: Vec<EventField>,
+}
+
+impl ReceiptQuery {
+    pub fn event<E: SolEvent>() -> Self {
+        ReceiptQuery {
+            event: E::SIGNATURE,
+            topic0: E::SIGNATURE_HASH.0,
+            topic_count: <E::TopicList as TopicList>::COUNT,
+            contract: None,
+            topics: vec![],
+            blocks: None,
+            fields: vec![],
+        }
+    }
+
+    /// Only the logs emitted by `contract`.
+    pub fn contract(mut self, contract: Address) -> Self {
+        self.contract = Some(contract);
+        self
+    }
+
+    /// Only the logs whose `topics[index]` is `value`.
+    pub fn topic(mut self, index: u32, value: Bytes32) -> Self {
+        self.topics.push((index, value));
+        self
+    }
+
+    /// Only the receipts of the blocks in `blocks`.
+    pub fn blocks(mut self, blocks: RangeInclusive<u32>) -> Self {
+        self.blocks = Some(blocks);
+        self
+    }
+
+    pub fn select(mut self, fields: impl IntoIterator<Item = EventField>) -> Self {
+        self.fields.extend(fields);
+        self
+    }
+
+    fn check(&self) -> Result<(), QueryError> {
+        if self.fields.is_empty() {
+            return Err(QueryError::NoFieldsSelected);
+        }
+        let topic_indexes = self.topics.iter().map(|(index, _)| *index);
+        let field_topic_indexes = self.fields.iter().filter_map(|field| match field {
+            EventField::Topic(index) => Some(*index),
+            EventField::Data(_) => None,
+        });
+        match topic_indexes
+            .chain(field_topic_indexes)
+            .find(|index| *index as usize >= self.topic_count)
+        {
+            Some(index) => Err(QueryError::TopicOutOfRange {
+                event: self.event,
+                index,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    pub fn matches(&self, log: &RawLog) -> bool {
+        log.topics.first() == Some(&self.topic0)
+            && self.contract.is_none_or(|contract| contract == log.address)
+            && self
+                .topics
+                .iter()
+                .all(|(index, value)| log.topics.get(*index as usize) == Some(value))
+    }
+
+    fn field_data(&self, log: &RawLog, field: EventField) -> Result<LogFieldData, QueryError> {
+        let out_of_range = QueryError::FieldOutOfRange {
+            log_pos: log.log_pos,
+            field,
+        };
+        let (is_topic, field_index, word) = match field {
+            EventField::Topic(index) => {
+                let topic = log.topics.get(index as usize).ok_or(out_of_range)?;
+                (true, index, *topic)
+            }
+            EventField::Data(index) => {
+                let start = index as usize * 32;
+                let word: Bytes32 = log
+                    .data
+                    .get(start..start + 32)
+                    .and_then(|word| word.try_into().ok())
+                    .ok_or(out_of_range)?;
+                (false, index, word)
+            }
+        };
+        Ok(LogFieldData {
+            contract: log.address,
+            topic: self.topic0,
+            log_pos: log.log_pos,
+            is_topic,
+            field_index,
+            value: U256::from_be_slice(&word),
+        })
+    }
+
+    pub fn apply(&self, receipts: &[RawReceipt]) -> Result<Vec<ReceiptData>, QueryError> {
+        self.check()?;
+        let mut selected = vec![];
+        for receipt in receipts {
+            if !self
+                .blocks
+                .as_ref()
+                .is_none_or(|blocks| blocks.contains(&receipt.block_num))
+            {
+                continue;
+            }
+            let mut fields = vec![];
+            for log in receipt.logs.iter().filter(|log| self.matches(log)) {
+                for field in &self.fields {
+                    fields.push(self.field_data(log, *field)?);
+                }
+            }
+            if !fields.is_empty() {
+                selected.push(ReceiptData::add_receipt(
+                    receipt.transaction_hash,
+                    receipt.block_num,
+                    receipt.block_base_fee,
+                    receipt.block_time,
+                    receipt.mpt_key_path,
+                    fields,
+                ));
+            }
+        }
+        Ok(selected)
+    }
+}
+
+/// Selects slots of one contract in the account storage read from a node, e.g. with
+/// `eth_getProof`, keeping one `AccountStorageData` per block so the selected slots of a
+/// block are requested in one storage query.
+///
+/// ```ignore
+/// let storage = StorageQuery::contract(pool)
+///     .blocks(21756800..=21756900)
+///     .select([slot0, slot1])
+///     .apply(&read_storage)?;
+/// let sdk = Builder::new().with_account_storage(storage);
+/// ```
+#[derive(Debug, Clone)]
+pub struct StorageQuery {
+    contract: Address,
+    blocks: Option<RangeInclusive<u32>>,
+    slots: Vec<Bytes32>,
+}
+
+impl StorageQuery {
+    pub fn contract(contract: Address) -> Self {
+        StorageQuery {
+            contract,
+            blocks: None,
+            slots: vec![],
+        }
+    }
+
+    /// Only the storage of the blocks in `blocks`.
+    pub fn blocks(mut self, blocks: RangeInclusive<u32>) -> Self {
+        self.blocks = Some(blocks);
+        self
+    }
+
+    pub fn select(mut self, slots: impl IntoIterator<Item = Bytes32>) -> Self {
+        self.slots.extend(slots);
+        self
+    }
+
+    pub fn matches(&self, storage: &AccountStorageData) -> bool {
+        storage.address == self.contract
+            && self
+                .blocks
+                .as_ref()
+                .is_none_or(|blocks| blocks.contains(&storage.block_num))
+    }
+
+    /// Fails if a selected slot was not read at a matching block.
+    pub fn apply(
+        &self,
+        storage: &[AccountStorageData],
+    ) -> Result<Vec<AccountStorageData>, QueryError> {
+        if self.slots.is_empty() {
+            return Err(QueryError::NoFieldsSelected);
+        }
+        let mut selected = vec![];
+        for storage in storage.iter().filter(|storage| self.matches(storage)) {
+            let slots = self
+                .slots
+                .iter()
+                .map(|slot| {
+                    storage
+                        .slots
+                        .iter()
+                        .find(|(read, _)| read == slot)
+                        .copied()
+                        .ok_or(QueryError::SlotNotFound {
+                            block_num: storage.block_num,
+                            slot: *slot,
+                        })
+                })
+                .collect::<Result<_, _>>()?;
+            selected.push(AccountStorageData::add_account_storage(
+                storage.block_num,
+                storage.block_base_fee,
+                storage.block_time,
+                storage.address,
+                slots,
+            ));
+        }
+        Ok(selected)
+    }
+}
+
+/// Selects transactions by sender, recipient and selector and turns them into
+/// `TransactionData` inputs with the selected calldata words.
+///
+/// ```ignore
+/// let transfers = TransactionQuery::new()
+///     .to(token)
+///     .selector([0xa9, 0x05, 0x9c, 0xbb])
+///     .blocks(21756800..=21756900)
+///     .select([0, 1])
+///     .apply(&raw_transactions)?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TransactionQuery {
+    from: Option<Address>,
+    to: Option<Address>,
+    selector: Option<[u8; 4]>,
+    blocks: Option<RangeInclusive<u32>>,
+    words: Vec<u32>,
+}
+
+impl TransactionQuery {
+    pub fn new() -> Self {
+        TransactionQuery::default()
+    }
+
+    /// Only the transactions sent by `from`.
+    pub fn from(mut self, from: Address) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    /// Only the transactions sent to `to`.
+    pub fn to(mut self, to: Address) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    /// Only the transactions calling the function with `selector`.
+    pub fn selector(mut self, selector: [u8; 4]) -> Self {
+        self.selector = Some(selector);
+        self
+    }
+
+    /// Only the transactions of the blocks in `blocks`.
+    pub fn blocks(mut self, blocks: RangeInclusive<u32>) -> Self {
+        self.blocks = Some(blocks);
+        self
+    }
+
+    /// The calldata words to add to each transaction, counted after the selector.
+    pub fn select(mut self, words: impl IntoIterator<Item = u32>) -> Self {
+        self.words.extend(words);
+        self
+    }
+
+    pub fn matches(&self, tx: &RawTransaction) -> bool {
+        self.from.is_none_or(|from| from == tx.from)
+            && self.to.is_none_or(|to| Some(to) == tx.to)
+            && self
+                .selector
+                .is_none_or(|selector| tx.input.len() >= 4 && selector == tx.selector())
+            && self
+                .blocks
+                .as_ref()
+                .is_none_or(|blocks| blocks.contains(&tx.block_num))
+    }
+
+    pub fn apply(&self, txs: &[RawTransaction]) -> Result<Vec<TransactionData>, QueryError> {
+        txs.iter()
+            .filter(|tx| self.matches(tx))
+            .map(|tx| tx.transaction_data(&self.words))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloy_sol_types::{sol, SolEvent};
+    use crypto_bigint::U256;
+
+    use super::{
+        QueryError, RawLog, RawReceipt, RawTransaction, ReceiptQuery, StorageQuery,
+        TransactionQuery,
+    };
+    use crate::input_types::{event::EventField, storage::AccountStorageData};
+
+    sol! {
+        event Swap(address indexed sender, address indexed recipient, int256 amount0, int256 amount1, uint160 sqrtPriceX96, uint128 liquidity, int24 tick);
+    }
+
+    const POOL: [u8; 20] = [0x88; 20];
+    const USER: [u8; 32] = [0x6a; 32];
+
+    fn swap_log(log_pos: u32, recipient: [u8; 32], amount0: u8) -> RawLog {
+        let mut data = vec![0; 5 * 32];
+        data[31] = amount0;
+        RawLog {
+            address: POOL,
+            topics: vec![Swap::SIGNATURE_HASH.0, [1; 32], recipient],
+            data,
+            log_pos,
+        }
+    }
+
+    fn raw_receipt(block_num: u32, logs: Vec<RawLog>) -> RawReceipt {
+        RawReceipt {
+            transaction_hash: [block_num as u8; 32],
+            block_num,
+            block_base_fee: U256::from_u32(7),
+            block_time: 1738475315,
+            mpt_key_path: 1,
+            logs,
+        }
+    }
+
+    #[test]
+    fn test_select_swaps() {
+        let mut other_pool = swap_log(3, USER, 9);
+        other_pool.address = [0; 20];
+        let receipts = vec![
+            raw_receipt(
+                10,
+                vec![swap_log(1, USER, 5), swap_log(2, [0; 32], 6), other_pool],
+            ),
+            raw_receipt(11, vec![swap_log(1, [0; 32], 7)]),
+            raw_receipt(20, vec![swap_log(1, USER, 8)]),
+        ];
+
+        let selected = ReceiptQuery::event::<Swap>()
+            .contract(POOL)
+            .topic(2, USER)
+            .blocks(10..=15)
+            .select([EventField::Data(0), EventField::Topic(2)])
+            .apply(&receipts)
+            .unwrap();
+        assert_eq!(selected.len(), 1);
+        let fields = &selected[0].fields;
+        assert_eq!(fields.len(), 2);
+        assert_eq!((fields[0].is_topic, fields[0].field_index), (false, 0));
+        assert_eq!(fields[0].value, U256::from_u32(5));
+        assert_eq!((fields[1].is_topic, fields[1].field_index), (true, 2));
+        assert_eq!(fields[1].value.to_be_bytes(), USER);
+        assert_eq!(selected[0].events::<Swap>(Some(POOL)).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_query() {
+        let receipts = vec![raw_receipt(10, vec![swap_log(1, USER, 5)])];
+        assert_eq!(
+            ReceiptQuery::event::<Swap>().apply(&receipts).err(),
+            Some(QueryError::NoFieldsSelected)
+        );
+        assert!(matches!(
+            ReceiptQuery::event::<Swap>()
+                .select([EventField::Topic(3)])
+                .apply(&receipts),
+            Err(QueryError::TopicOutOfRange { index: 3, .. })
+        ));
+        assert_eq!(
+            ReceiptQuery::event::<Swap>()
+                .select([EventField::Data(5)])
+                .apply(&receipts)
+                .err(),
+            Some(QueryError::FieldOutOfRange {
+                log_pos: 1,
+                field: EventField::Data(5)
+            })
+        );
+    }
+
+    #[test]
+    fn test_select_slots() {
+        let storage = |block_num, address| {
+            AccountStorageData::add_account_storage(
+                block_num,
+                U256::from_u32(7),
+                1738475315,
+                address,
+                vec![
+                    ([1; 32], U256::from_u32(block_num)),
+                    ([2; 32], U256::from_u32(2)),
+                ],
+            )
+        };
+        let read = vec![storage(10, POOL), storage(11, [0; 20]), storage(20, POOL)];
+
+        let selected = StorageQuery::contract(POOL)
+            .blocks(10..=15)
+            .select([[2; 32], [1; 32]])
+            .apply(&read)
+            .unwrap();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].block_num, 10);
+        assert_eq!(
+            selected[0].slots,
+            vec![([2; 32], U256::from_u32(2)), ([1; 32], U256::from_u32(10))]
+        );
+
+        assert_eq!(
+            StorageQuery::contract(POOL).apply(&read).err(),
+            Some(QueryError::NoFieldsSelected)
+        );
+        assert_eq!(
+            StorageQuery::contract(POOL)
+                .select([[3; 32]])
+                .apply(&read)
+                .err(),
+            Some(QueryError::SlotNotFound {
+                block_num: 10,
+                slot: [3; 32]
+            })
+        );
+    }
+
+    #[test]
+    fn test_select_transactions() {
+        let transfer = |hash: u8, from, amount: u8| {
+            let mut input = vec![0xa9, 0x05, 0x9c, 0xbb];
+            input.extend([0; 64]);
+            input[4 + 63] = amount;
+            RawTransaction {
+                hash: [hash; 32],
+                block_num: 10,
+                block_base_fee: U256::from_u32(7),
+                block_time: 1738475315,
+                mpt_key_path: hash as u32,
+                leaf_hash: [0xee; 32],
+                from,
+                to: Some(POOL),
+                value: U256::ZERO,
+                nonce: 1,
+                gas_price: U256::from_u32(8),
+                gas_tip_cap: U256::from_u32(1),
+                input,
+            }
+        };
+        let mut approve = transfer(3, [1; 20], 9);
+        approve.input[..4].copy_from_slice(&[0x09, 0x5e, 0xa7, 0xb3]);
+        let txs = vec![transfer(1, [1; 20], 5), transfer(2, [2; 20], 6), approve];
+
+        let selected = TransactionQuery::new()
+            .from([1; 20])
+            .to(POOL)
+            .selector([0xa9, 0x05, 0x9c, 0xbb])
+            .select([1])
+            .apply(&txs)
+            .unwrap();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].hash, [1; 32]);
+        assert_eq!(selected[0].leaf_hash, [0xee; 32]);
+        assert_eq!(selected[0].unverified_calldata_word(1), Some(U256::from_u32(5)));
+        assert_eq!(selected[0].unverified_selector(), [0xa9, 0x05, 0x9c, 0xbb]);
+
+        assert_eq!(
+            TransactionQuery::new().select([2]).apply(&txs).err(),
+            Some(QueryError::CalldataOutOfRange {
+                hash: [1; 32],
+                word_index: 2
+            })
+        );
+    }
+}
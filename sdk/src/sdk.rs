@@ -1,36 +1,30 @@
-use std::{collections::HashMap, fs, path::PathBuf};
+#[cfg(not(target_os = "zkvm"))]
+use std::path::{Path, PathBuf};
 
-use anyhow::{Error, Ok};
+use alloy_sol_types::{SolStruct, SolValue};
+#[cfg(not(target_os = "zkvm"))]
+use anyhow::Error;
 use crypto_bigint::{Zero, U256};
 use p3_baby_bear::BabyBear;
 use p3_field::PrimeField32;
 use serde::{Deserialize, Serialize};
 
-const RECEIPT_DUMMYS: [(u64, &str); 1] = [
-    (
-        1,
-        "0x49d4b43790b25474",
-    )
-];
-
-const STORAGE_DUMMYS: [(u64, &str); 1] = [
-    (
-        1,
-        "0x4e93157937ed2e80",
-    )
-];
-
-const TX_DUMMYS: [(u64, &str); 1] = [
-    (
-        1,
-        "0x46af2cafe7df8b99",
-    )
-];
-
+pub use crate::dummy::DummyCommitment;
+#[cfg(not(target_os = "zkvm"))]
+use crate::inputs_file::{InputsFile, InputsFileError, INPUTS_FILE_NAME};
 use crate::{
+    commitment::CommitmentVersion,
     data_types::hash_out::MerkleInput,
-    input_types::{receipt::ReceiptData, storage::StorageSlotData, transaction::TransactionData},
-    inputs_merkle::build_merkle_tree,
+    error::SdkError,
+    input_types::{
+        account::AccountData,
+        block_header::BlockHeaderData,
+        receipt::ReceiptData,
+        storage::{AccountStorageData, StorageSlotData},
+        transaction::TransactionData,
+    },
+    inputs_merkle::{build_merkle_tree, MerkleTree},
+    output::encode_output,
 };
 
 #[derive(Default)]
@@ -39,6 +33,13 @@ pub struct Builder {
     pub receipts: Option<Vec<ReceiptData>>,
     pub storage_slots: Option<Vec<StorageSlotData>>,
     pub transactions: Option<Vec<TransactionData>>,
+    // private so block headers and accounts are only set through the feature gated setters
+    block_headers: Option<Vec<BlockHeaderData>>,
+    max_block_header_size: u32,
+    accounts: Option<Vec<AccountData>>,
+    max_account_size: u32,
+    pub commitment_version: CommitmentVersion,
+    pub dummy_commitments: Option<DummyCommitment>,
 }
 
 impl Builder {
@@ -46,13 +47,48 @@ impl Builder {
         Builder::default()
     }
 
+    /// Sets the chain the inputs are read from. It selects the dummy commitments used to pad
+    /// the input commitment tree, so it must be set before `init` and be registered, see
+    /// `DummyCommitment::register_chain`, unless `with_dummy_commitments` is used.
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Pads the input commitment tree with `dummy_commitments` instead of the ones registered
+    /// for the chain.
+    pub fn with_dummy_commitments(mut self, dummy_commitments: DummyCommitment) -> Self {
+        self.dummy_commitments = Some(dummy_commitments);
+        self
+    }
+
+    /// Sets how the input leaves are committed, `CommitmentVersion::V0` by default. The gateway
+    /// only proves `V0` leaves so far.
+    pub fn with_commitment_version(mut self, commitment_version: CommitmentVersion) -> Self {
+        self.commitment_version = commitment_version;
+        self
+    }
+
     pub fn with_receipts(mut self, receipts: Vec<ReceiptData>) -> Self {
         self.receipts = Some(receipts);
         self
     }
 
+    /// Adds storage slots after the ones already added, like `with_account_storage`.
     pub fn with_storage_slots(mut self, storage_slots: Vec<StorageSlotData>) -> Self {
-        self.storage_slots = Some(storage_slots);
+        self.storage_slots
+            .get_or_insert_with(Vec::new)
+            .extend(storage_slots);
+        self
+    }
+
+    /// Adds the slots of each account as storage slots, after the ones already added.
+    /// Each slot is its own leaf, the slots of an account are requested in one storage query.
+    pub fn with_account_storage(mut self, accounts: Vec<AccountStorageData>) -> Self {
+        let storage_slots = self.storage_slots.get_or_insert_with(Vec::new);
+        accounts
+            .iter()
+            .for_each(|account| storage_slots.extend(account.storage_slots()));
         self
     }
 
@@ -61,47 +97,141 @@ impl Builder {
         self
     }
 
-    pub fn init(self, max_receipt_size: u32, max_storage_size: u32, max_tx_size: u32) -> SDK {
-        if let Some(receipts) = self.receipts.clone() {
+    /// Block headers have no gateway query yet, so their leaves can't be proven until there
+    /// is one. Requires the `block-headers` feature.
+    #[cfg(feature = "block-headers")]
+    pub fn with_block_headers(mut self, block_headers: Vec<BlockHeaderData>) -> Self {
+        self.block_headers = Some(block_headers);
+        self
+    }
+
+    /// Sets the max block header size, 0 by default. Block headers take the leaves
+    /// after the transactions, so it counts in the total max size passed to `init`.
+    #[cfg(feature = "block-headers")]
+    pub fn with_max_block_header_size(mut self, max_block_header_size: u32) -> Self {
+        self.max_block_header_size = max_block_header_size;
+        self
+    }
+
+    /// Accounts have no gateway query yet, so their leaves can't be proven until there is
+    /// one. Requires the `accounts` feature.
+    #[cfg(feature = "accounts")]
+    pub fn with_accounts(mut self, accounts: Vec<AccountData>) -> Self {
+        self.accounts = Some(accounts);
+        self
+    }
+
+    /// Sets the max account size, 0 by default. Accounts take the leaves after the
+    /// block headers, so it counts in the total max size passed to `init`.
+    #[cfg(feature = "accounts")]
+    pub fn with_max_account_size(mut self, max_account_size: u32) -> Self {
+        self.max_account_size = max_account_size;
+        self
+    }
+
+    /// Validates the inputs against the max sizes and initializes the SDK.
+    /// Each max size must be a multiple of 32 and their sum a power of two.
+    pub fn try_init(
+        mut self,
+        max_receipt_size: u32,
+        max_storage_size: u32,
+        max_tx_size: u32,
+    ) -> Result<SDK, SdkError> {
+        if self.chain_id == 0 {
+            return Err(SdkError::MissingChainId);
+        }
+
+        for (category, size) in [
+            ("receipt", max_receipt_size),
+            ("storage", max_storage_size),
+            ("transaction", max_tx_size),
+            ("block header", self.max_block_header_size),
+            ("account", self.max_account_size),
+        ] {
+            if size % 32 != 0 {
+                return Err(SdkError::SizeNotMultipleOf32 { category, size });
+            }
+        }
+
+        let leaves_size = max_receipt_size as u64
+            + max_storage_size as u64
+            + max_tx_size as u64
+            + self.max_block_header_size as u64
+            + self.max_account_size as u64;
+        if !leaves_size.is_power_of_two() {
+            return Err(SdkError::LeafCountNotPowerOfTwo(leaves_size));
+        }
+
+        if let Some(receipts) = &self.receipts {
             if receipts.len() > max_receipt_size as usize {
-                panic!(
-                    "receipts length exceeds the max receipt size: {:?}",
-                    max_receipt_size
-                );
+                return Err(SdkError::TooManyReceipts {
+                    len: receipts.len(),
+                    max: max_receipt_size,
+                });
             }
         }
 
-        if let Some(storage_slots) = self.storage_slots.clone() {
+        if let Some(storage_slots) = &self.storage_slots {
             if storage_slots.len() > max_storage_size as usize {
-                panic!(
-                    "receipts length exceeds the max storage size: {:?}",
-                    max_storage_size
-                );
+                return Err(SdkError::TooManyStorageSlots {
+                    len: storage_slots.len(),
+                    max: max_storage_size,
+                });
             }
         }
 
-        if let Some(transactions) = self.transactions.clone() {
+        if let Some(transactions) = &self.transactions {
             if transactions.len() > max_tx_size as usize {
-                panic!(
-                    "receipts length exceeds the max transaction size: {:?}",
-                    max_tx_size
-                );
+                return Err(SdkError::TooManyTransactions {
+                    len: transactions.len(),
+                    max: max_tx_size,
+                });
             }
         }
 
-        SDK::new(
-            self.chain_id,
+        if let Some(block_headers) = &self.block_headers {
+            if block_headers.len() > self.max_block_header_size as usize {
+                return Err(SdkError::TooManyBlockHeaders {
+                    len: block_headers.len(),
+                    max: self.max_block_header_size,
+                });
+            }
+        }
+
+        if let Some(accounts) = &self.accounts {
+            if accounts.len() > self.max_account_size as usize {
+                return Err(SdkError::TooManyAccounts {
+                    len: accounts.len(),
+                    max: self.max_account_size,
+                });
+            }
+        }
+
+        let dummy_commitments = match self.dummy_commitments.take() {
+            Some(dummy_commitments) => dummy_commitments,
+            None => DummyCommitment::try_get_dummy_commitment_with(
+                self.chain_id,
+                self.commitment_version,
+            )?,
+        };
+
+        Ok(SDK::new(
+            self,
             max_receipt_size,
             max_storage_size,
             max_tx_size,
-            self.receipts,
-            self.storage_slots,
-            self.transactions,
-        )
+            dummy_commitments,
+        ))
+    }
+
+    /// Same as `try_init`, but panics if the inputs are invalid.
+    pub fn init(self, max_receipt_size: u32, max_storage_size: u32, max_tx_size: u32) -> SDK {
+        self.try_init(max_receipt_size, max_storage_size, max_tx_size)
+            .unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
-#[derive(Default, Serialize, Deserialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct SDK {
     pub chain_id: u64,
     pub max_receipt_size: u32,
@@ -110,72 +240,50 @@ pub struct SDK {
     pub receipts: Option<Vec<ReceiptData>>,
     pub storage_slots: Option<Vec<StorageSlotData>>,
     pub transactions: Option<Vec<TransactionData>>,
+    #[serde(default)]
+    pub max_block_header_size: u32,
+    #[serde(default)]
+    pub block_headers: Option<Vec<BlockHeaderData>>,
+    #[serde(default)]
+    pub max_account_size: u32,
+    #[serde(default)]
+    pub accounts: Option<Vec<AccountData>>,
+    /// Missing in inputs saved before commitments were versioned, which are `V0`
+    #[serde(default)]
+    pub commitment_version: CommitmentVersion,
     pub dummy_commitments: DummyCommitment,
     pub input_commitments: MerkleInput,
+    /// ABI encoding of the input commitment and the output struct, set by `set_output`
+    #[serde(default)]
+    pub output: Option<Vec<u8>>,
     /// Indicate whether input_commitment has been committed
     commited: bool,
 }
 
-#[derive(Clone, Default, Serialize, Deserialize)]
-pub struct DummyCommitment {
-    pub receipt_dummy_commitment: [u32; 8],
-    pub storage_dummy_commitment: [u32; 8],
-    pub tx_dummy_commitment: [u32; 8],
-}
-
-impl DummyCommitment {
-    fn from_str(hex_str: &str) -> [u32; 8] {
-        let bytes = hex::decode(&hex_str[2..]).unwrap();
-        let dummy_fields = bytes
-            .chunks_exact(4)
-            .map(|chunk| (chunk[0] as u32) * 256 + chunk[1] as u32)
-            .collect::<Vec<_>>();
-        dummy_fields.try_into().unwrap()
-    }
-
-    pub fn get_dummy_commitment(chain_id: u64) -> DummyCommitment {
-        let zero_dummy_hex = "0x0000000000000000000000000000000000000000000000000000000000000000";
-        let receipt_dummy_map: HashMap<u64, &str> = RECEIPT_DUMMYS.iter().cloned().collect();
-        let receipt_dummy = receipt_dummy_map.get(&chain_id).unwrap_or(&zero_dummy_hex);
-        let receipt_dummy_commitment = Self::from_str(receipt_dummy);
-
-        let storage_dummy_map: HashMap<u64, &str> = STORAGE_DUMMYS.iter().cloned().collect();
-        let storage_dummy = storage_dummy_map.get(&chain_id).unwrap_or(&zero_dummy_hex);
-        let storage_dummy_commitment = Self::from_str(&storage_dummy);
-
-        let tx_dummy_map: HashMap<u64, &str> = TX_DUMMYS.iter().cloned().collect();
-        let tx_dummy = tx_dummy_map.get(&chain_id).unwrap_or(&zero_dummy_hex);
-        let tx_dummy_commitment = Self::from_str(&tx_dummy);
-
-        DummyCommitment {
-            receipt_dummy_commitment,
-            storage_dummy_commitment,
-            tx_dummy_commitment,
-        }
-    }
-}
-
 impl SDK {
     fn new(
-        chain_id: u64,
+        builder: Builder,
         max_receipt_size: u32,
         max_storage_size: u32,
         max_tx_size: u32,
-        receipts: Option<Vec<ReceiptData>>,
-        storage_slots: Option<Vec<StorageSlotData>>,
-        transactions: Option<Vec<TransactionData>>,
+        dummy_commitments: DummyCommitment,
     ) -> Self {
-        let dummy_commitments = DummyCommitment::get_dummy_commitment(chain_id);
         let mut sdk = SDK {
-            chain_id,
+            chain_id: builder.chain_id,
             max_receipt_size,
             max_storage_size,
             max_tx_size,
-            receipts,
-            storage_slots,
-            transactions,
+            receipts: builder.receipts,
+            storage_slots: builder.storage_slots,
+            transactions: builder.transactions,
+            max_block_header_size: builder.max_block_header_size,
+            block_headers: builder.block_headers,
+            max_account_size: builder.max_account_size,
+            accounts: builder.accounts,
+            commitment_version: builder.commitment_version,
             dummy_commitments,
             input_commitments: MerkleInput::default(),
+            output: None,
             commited: false,
         };
         sdk.commit_inputs();
@@ -184,15 +292,34 @@ impl SDK {
 
     /// Commits all the inputs and get the root hash
     fn commit_inputs(&mut self) {
-        let leaves_size = self.max_receipt_size + self.max_storage_size + self.max_tx_size;
+        let leaves = self.input_leaves();
+        // build merkle root
+        let root = build_merkle_tree(&leaves);
+        self.input_commitments = root.hash;
+    }
+
+    /// Rebuilds the input commitment tree, keeping every level so that inclusion proofs
+    /// can be produced. Receipts occupy leaves `[0, max_receipt_size)`, followed by the
+    /// storage slots, the transactions, the block headers and then the accounts, each section
+    /// padded with its dummy commitment.
+    pub fn merkle_tree(&self) -> MerkleTree {
+        MerkleTree::new(&self.input_leaves())
+    }
+
+    /// Commits every input and pads each section up to its max size.
+    fn input_leaves(&self) -> Vec<MerkleInput> {
+        let leaves_size = self.max_receipt_size
+            + self.max_storage_size
+            + self.max_tx_size
+            + self.max_block_header_size
+            + self.max_account_size;
         let mut leaves = Vec::with_capacity(leaves_size as usize);
 
         // commit receipts and pad with dummy receipts
         if let Some(receipts) = self.receipts.clone() {
             receipts.iter().for_each(|d| {
-                leaves.push(d.commit_inputs());
+                leaves.push(d.commit_inputs_with(self.commitment_version));
             });
-            println!("receipt commitment: {:?}", leaves[0])
         }
         for _ in leaves.len()..self.max_receipt_size as usize {
             leaves.push(self.dummy_commitments.receipt_dummy_commitment);
@@ -201,7 +328,7 @@ impl SDK {
         // commit storage and pad with dummy storage
         if let Some(storages) = self.storage_slots.clone() {
             storages.iter().for_each(|d| {
-                leaves.push(d.commit_inputs());
+                leaves.push(d.commit_inputs_with(self.commitment_version));
             });
         }
         for _ in leaves.len()..self.max_receipt_size as usize + self.max_storage_size as usize {
@@ -211,7 +338,7 @@ impl SDK {
         // commit transactions and pad with dummy transactions.
         if let Some(transactions) = self.transactions.clone() {
             transactions.iter().for_each(|d| {
-                leaves.push(d.commit_inputs());
+                leaves.push(d.commit_inputs_with(self.commitment_version));
             });
         }
         for _ in leaves.len()
@@ -221,25 +348,68 @@ impl SDK {
         {
             leaves.push(self.dummy_commitments.tx_dummy_commitment);
         }
-        // println!("leaves length: {:?}", leaves.len());
-        // build merkle root
-        let root = build_merkle_tree(&leaves);
-        self.input_commitments = root.hash;
+
+        // commit block headers and pad with dummy block headers
+        if let Some(block_headers) = self.block_headers.clone() {
+            block_headers.iter().for_each(|d| {
+                leaves.push(d.commit_inputs_with(self.commitment_version));
+            });
+        }
+        for _ in leaves.len()..(leaves_size - self.max_account_size) as usize {
+            leaves.push(self.dummy_commitments.block_header_dummy_commitment);
+        }
+
+        // commit accounts and pad with dummy accounts
+        if let Some(accounts) = self.accounts.clone() {
+            accounts.iter().for_each(|d| {
+                leaves.push(d.commit_inputs_with(self.commitment_version));
+            });
+        }
+        for _ in leaves.len()..leaves_size as usize {
+            leaves.push(self.dummy_commitments.account_dummy_commitment);
+        }
+        leaves
     }
 
     /// Save the input data inputs into file, prepare for proving the input data by brevis zk
-    /// this function can't be used in the riscv program
+    /// this function can't be used in the riscv program, use `save_inputs_as` to choose the file name
     /// once the inputs be saved, use this file to request proving the inputs REQUEST_DATA_FILE=file-path brevis-request
+    #[cfg(not(target_os = "zkvm"))]
     pub fn save_inputs(&self, dir: PathBuf) -> anyhow::Result<PathBuf, Error> {
-        let file_path = dir.join("request_prove_inputs.json");
-        let file = fs::File::create(file_path.clone()).expect("create request file failed");
-        serde_json::to_writer(file, self)?;
+        let file_path = dir.join(INPUTS_FILE_NAME);
+        self.save_inputs_as(&file_path)?;
         Ok(file_path)
     }
 
+    /// Saves the inputs to `path` in an `InputsFile`, with the recomputed input commitment
+    /// and a checksum in its header. Fails without writing if the inputs changed after `init`,
+    /// like `load_inputs` would.
+    #[cfg(not(target_os = "zkvm"))]
+    pub fn save_inputs_as(&self, path: impl AsRef<Path>) -> Result<(), InputsFileError> {
+        let file = InputsFile::new(self.clone())?;
+        file.check()?;
+        file.write(path)
+    }
+
+    /// Loads inputs saved by `save_inputs`, failing if the header, the checksum or the input
+    /// commitment don't match the inputs.
+    #[cfg(not(target_os = "zkvm"))]
+    pub fn load_inputs(path: impl AsRef<Path>) -> Result<SDK, InputsFileError> {
+        InputsFile::read(path)?.verify()
+    }
+
+    /// Encodes `output` after the input commitment, see `output::encode_output`, and keeps it
+    /// for the proving request. Returns the bytes to commit, e.g. with
+    /// `pico_sdk::io::commit_coprocessor_bytes`.
+    pub fn set_output<T: SolStruct + SolValue + Clone>(&mut self, output: &T) -> Vec<u8> {
+        let output = encode_output(&self.input_commitments, output);
+        self.output = Some(output.clone());
+        output
+    }
+
     pub fn sum_of_u256(&self, values: Vec<U256>) -> U256 {
         let mut sum: U256 = U256::zero();
-        values.iter().for_each(|v| {    
+        values.iter().for_each(|v| {
             sum = sum.add_mod(v, &U256::from_u32(BabyBear::ORDER_U32));
         });
         sum
@@ -249,8 +419,233 @@ impl SDK {
         self.commited = is_commited
     }
 
-    pub fn is_commited(&self) -> bool{
+    pub fn is_commited(&self) -> bool {
         self.commited
     }
+}
+
+#[cfg(test)]
+mod test {
+    use crypto_bigint::U256;
+
+    use crate::{
+        commitment::CommitmentVersion,
+        dummy::DummyInputs,
+        error::SdkError,
+        input_types::{
+            receipt::ReceiptData,
+            storage::{AccountStorageData, StorageSlotData},
+        },
+    };
+
+    use super::{Builder, DummyCommitment};
+
+    fn test_receipt() -> ReceiptData {
+        ReceiptData::add_receipt([1; 32], 21135753, U256::from_u32(7), 1732158058, 1, vec![])
+    }
+
+    #[test]
+    fn test_try_init_rejects_invalid_layouts() {
+        assert_eq!(
+            Builder::new().try_init(32, 0, 0).err(),
+            Some(SdkError::MissingChainId)
+        );
+        assert_eq!(
+            Builder::new().with_chain_id(1).try_init(32, 16, 16).err(),
+            Some(SdkError::SizeNotMultipleOf32 {
+                category: "storage",
+                size: 16
+            })
+        );
+        assert_eq!(
+            Builder::new().with_chain_id(1).try_init(32, 32, 32).err(),
+            Some(SdkError::LeafCountNotPowerOfTwo(96))
+        );
+        assert_eq!(
+            Builder::new()
+                .with_chain_id(1)
+                .with_receipts(vec![test_receipt(); 33])
+                .try_init(32, 0, 0)
+                .err(),
+            Some(SdkError::TooManyReceipts { len: 33, max: 32 })
+        );
+    }
+
+    #[test]
+    fn test_try_init() {
+        let sdk = Builder::new()
+            .with_chain_id(1)
+            .with_receipts(vec![test_receipt(); 2])
+            .try_init(32, 32, 0)
+            .unwrap();
+        assert_eq!(sdk.merkle_tree().leaves().len(), 64);
+        assert_eq!(sdk.merkle_tree().root(), sdk.input_commitments);
+    }
+
+    #[test]
+    fn test_storage_slots_are_appended() {
+        let slot = |block_num| {
+            StorageSlotData::add_storage_slot(
+                block_num,
+                U256::from_u32(7),
+                1732158058,
+                [1; 20],
+                [2; 32],
+                U256::from_u32(3),
+            )
+        };
+        let account = AccountStorageData::add_account_storage(
+            2,
+            U256::from_u32(7),
+            1732158058,
+            [1; 20],
+            vec![([2; 32], U256::from_u32(3)), ([4; 32], U256::from_u32(5))],
+        );
+        let builder = Builder::new()
+            .with_storage_slots(vec![slot(1)])
+            .with_account_storage(vec![account])
+            .with_storage_slots(vec![slot(3)]);
+        let block_nums: Vec<u32> = builder
+            .storage_slots
+            .unwrap()
+            .iter()
+            .map(|slot| slot.block_num)
+            .collect();
+        assert_eq!(block_nums, vec![1, 2, 2, 3]);
+    }
+
+    #[test]
+    fn test_chain_id_selects_dummy_commitments() {
+        let sdk = Builder::new().with_chain_id(1).init(32, 0, 0);
+        assert_eq!(sdk.chain_id, 1);
+        assert_eq!(
+            sdk.dummy_commitments.receipt_dummy_commitment,
+            DummyCommitment::get_dummy_commitment(1).receipt_dummy_commitment
+        );
+
+        let mut dummies = DummyInputs::default();
+        dummies.receipt.block_time = 1;
+        let custom = Builder::new()
+            .with_chain_id(5)
+            .with_dummy_commitments(dummies.commit())
+            .init(32, 0, 0);
+        assert_eq!(custom.dummy_commitments, dummies.commit());
+        assert_ne!(sdk.input_commitments, custom.input_commitments);
+        assert_eq!(
+            Builder::new().with_chain_id(6).try_init(32, 0, 0).err(),
+            Some(SdkError::UnsupportedChain(6))
+        );
+    }
 
+    #[test]
+    #[cfg(feature = "block-headers")]
+    fn test_block_headers() {
+        use crate::input_types::block_header::BlockHeaderData;
+
+        let header = BlockHeaderData::add_block_header(
+            21135753,
+            U256::from_u32(7),
+            1732158058,
+            [1; 32],
+            [2; 32],
+        );
+        assert_eq!(
+            Builder::new()
+                .with_chain_id(1)
+                .with_block_headers(vec![header.clone()])
+                .try_init(32, 0, 0)
+                .err(),
+            Some(SdkError::TooManyBlockHeaders { len: 1, max: 0 })
+        );
+        assert_eq!(
+            Builder::new()
+                .with_chain_id(1)
+                .with_max_block_header_size(32)
+                .try_init(64, 0, 0)
+                .err(),
+            Some(SdkError::LeafCountNotPowerOfTwo(96))
+        );
+
+        let sdk = Builder::new()
+            .with_chain_id(1)
+            .with_block_headers(vec![header.clone()])
+            .with_max_block_header_size(32)
+            .init(32, 0, 0);
+        let leaves = sdk.merkle_tree().leaves().to_vec();
+        assert_eq!(leaves.len(), 64);
+        assert_eq!(leaves[32], header.commit_inputs());
+        assert_eq!(
+            leaves[33],
+            sdk.dummy_commitments.block_header_dummy_commitment
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "accounts")]
+    fn test_accounts() {
+        use crate::input_types::account::AccountData;
+
+        let account =
+            AccountData::add_account([1; 20], 21135753, U256::from_u32(7), 3, [2; 32], [3; 32]);
+        assert_eq!(
+            Builder::new()
+                .with_chain_id(1)
+                .with_accounts(vec![account.clone(); 2])
+                .with_max_account_size(0)
+                .try_init(32, 0, 0)
+                .err(),
+            Some(SdkError::TooManyAccounts { len: 2, max: 0 })
+        );
+
+        let sdk = Builder::new()
+            .with_chain_id(1)
+            .with_accounts(vec![account.clone()])
+            .with_max_account_size(32)
+            .init(64, 32, 0);
+        let leaves = sdk.merkle_tree().leaves().to_vec();
+        assert_eq!(leaves.len(), 128);
+        assert_eq!(leaves[95], sdk.dummy_commitments.storage_dummy_commitment);
+        assert_eq!(leaves[96], account.commit_inputs());
+        assert_eq!(leaves[97], sdk.dummy_commitments.account_dummy_commitment);
+    }
+
+    #[test]
+    fn test_commitment_versions() {
+        let v0 = Builder::new()
+            .with_chain_id(1)
+            .with_receipts(vec![test_receipt()])
+            .init(32, 0, 0);
+        assert_eq!(v0.commitment_version, CommitmentVersion::V0);
+
+        let v1 = Builder::new()
+            .with_chain_id(1)
+            .with_receipts(vec![test_receipt()])
+            .with_commitment_version(CommitmentVersion::V1)
+            .init(32, 0, 0);
+        assert_ne!(v0.input_commitments, v1.input_commitments);
+        let leaves = v1.merkle_tree().leaves().to_vec();
+        assert_eq!(
+            leaves[0],
+            test_receipt().commit_inputs_with(CommitmentVersion::V1)
+        );
+        assert_eq!(
+            leaves[1],
+            DummyInputs::default()
+                .commit_with(CommitmentVersion::V1)
+                .receipt_dummy_commitment
+        );
+        assert_ne!(leaves[1], v0.dummy_commitments.receipt_dummy_commitment);
+    }
+
+    #[test]
+    #[should_panic(expected = "transactions length 1 exceeds the max transaction size 0")]
+    fn test_init_panics() {
+        use crate::input_types::transaction::TransactionData;
+
+        let tx = TransactionData::add_transaction([1; 32], 1, U256::from_u32(1), 1, 0, [2; 32]);
+        Builder::new()
+            .with_chain_id(1)
+            .with_transactions(vec![tx])
+            .init(32, 0, 0);
+    }
 }
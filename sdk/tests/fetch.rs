@@ -0,0 +1,207 @@
+#![cfg(feature = "fetch")]
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use alloy_primitives::keccak256;
+use alloy_sol_types::{sol, SolEvent};
+use coprocessor_sdk::{
+    fetch::{FetchError, RpcFetcher},
+    input_types::event::EventField,
+    mpt::verify_proof,
+    query::{QueryError, ReceiptQuery},
+};
+use crypto_bigint::U256;
+use serde_json::{json, Value};
+
+sol! {
+    event Swap(address indexed sender, address indexed recipient, int256 amount0, int256 amount1, uint160 sqrtPriceX96, uint128 liquidity, int24 tick);
+}
+
+const TX: [u8; 32] = [0xd9; 32];
+// the transaction at index 1 of `BLOCK`, whose raw transactions are recorded
+const TRANSFER: &str = "0x5fcc8367d98b991abd65cb5dc1188c0dff3585623e8640711b46d16445187fc0";
+const TRANSACTIONS_ROOT: &str =
+    "0x29250570c1951802bf740d79afe5287956672adc0f839ed03564f82ca9720068";
+const POOL: [u8; 20] = [0x88; 20];
+const BLOCK: u32 = 21756846;
+
+/// Serves the responses recorded in `tests/data/rpc_recording.json`, matched on the method
+/// and the params of the request, and returns the node URL and the requests served so far.
+fn serve_recording() -> (String, Arc<Mutex<Vec<Value>>>) {
+    let requests = Arc::new(Mutex::new(vec![]));
+    let served = requests.clone();
+    let recording: Vec<Value> =
+        serde_json::from_str(include_str!("data/rpc_recording.json")).unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let request: Value = serde_json::from_slice(&body).unwrap();
+            served.lock().unwrap().push(request.clone());
+
+            let mut response = json!({ "jsonrpc": "2.0", "id": request["id"] });
+            match recording.iter().find(|recorded| {
+                recorded["method"] == request["method"] && recorded["params"] == request["params"]
+            }) {
+                Some(recorded) if recorded.get("error").is_some() => {
+                    response["error"] = recorded["error"].clone()
+                }
+                Some(recorded) => response["result"] = recorded["result"].clone(),
+                None => response["error"] = json!({ "code": -32601, "message": "not recorded" }),
+            }
+            let body = response.to_string();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        }
+    });
+    (url, requests)
+}
+
+#[test]
+fn test_fetch_receipts() {
+    let fetcher = RpcFetcher::new(serve_recording().0);
+
+    let raw_receipt = fetcher.raw_receipt(TX).unwrap();
+    assert_eq!(raw_receipt.block_num, BLOCK);
+    assert_eq!(raw_receipt.block_base_fee, U256::from_u64(0x1e2cc31f9));
+    assert_eq!(raw_receipt.block_time, 1738466355);
+    assert_eq!(raw_receipt.mpt_key_path, 1);
+    assert_eq!(raw_receipt.logs.len(), 2);
+    // the position in the receipt, the proof doesn't see the logIndex 0x10 and 0x11 in the block
+    assert_eq!(raw_receipt.logs[0].log_pos, 0);
+    assert_eq!(raw_receipt.logs[1].log_pos, 1);
+
+    let receipts = fetcher
+        .receipts(
+            &[TX],
+            &ReceiptQuery::event::<Swap>()
+                .contract(POOL)
+                .select([EventField::Topic(2), EventField::Data(1)]),
+        )
+        .unwrap();
+    assert_eq!(receipts.len(), 1);
+    let swap = receipts[0].event::<Swap>(Some(POOL)).unwrap();
+    assert_eq!(swap.log_pos, 1);
+    assert_eq!(swap.value(EventField::Data(1)), Ok(U256::from_u32(2500)));
+    assert_eq!(
+        swap.value(EventField::Topic(2)).unwrap().to_be_bytes()[12..],
+        [0x6a; 20]
+    );
+    assert_eq!(receipts[0].fields[0].topic, Swap::SIGNATURE_HASH.0);
+
+    assert!(matches!(
+        fetcher.raw_receipt([0x3c; 32]),
+        Err(FetchError::NotFound(_))
+    ));
+}
+
+#[test]
+fn test_fetch_storage_slot() {
+    let fetcher = RpcFetcher::new(serve_recording().0);
+
+    let slot = fetcher.storage_slot(POOL, [0; 32], BLOCK).unwrap();
+    assert_eq!(slot.block_num, BLOCK);
+    assert_eq!(slot.block_time, 1738466355);
+    assert_eq!(slot.value, U256::ONE.shl_vartime(216));
+
+    let mut missing = [0; 32];
+    missing[31] = 1;
+    match fetcher.storage_slot(POOL, missing, BLOCK) {
+        Err(FetchError::Rpc { code, message }) => {
+            assert_eq!(code, -32000);
+            assert_eq!(message, "header not found");
+        }
+        other => panic!("unexpected {:?}", other.map(|slot| slot.value)),
+    }
+}
+
+fn hash(hex: &str) -> [u8; 32] {
+    hex::decode(hex.trim_start_matches("0x"))
+        .unwrap()
+        .try_into()
+        .unwrap()
+}
+
+#[test]
+fn test_fetch_transaction() {
+    let (url, requests) = serve_recording();
+    let fetcher = RpcFetcher::new(url);
+
+    let tx = fetcher.transaction(hash(TRANSFER), &[1]).unwrap();
+    assert_eq!(tx.hash, hash(TRANSFER));
+    assert_eq!((tx.block_num, tx.mpt_key_path), (BLOCK, 1));
+    assert_eq!(tx.block_base_fee, U256::from_u64(0x1e2cc31f9));
+    assert_eq!(
+        (tx.unverified_from(), tx.unverified_to()),
+        ([0x11; 20], Some([0x22; 20]))
+    );
+    assert_eq!(tx.unverified_nonce(), 42);
+    assert_eq!(tx.unverified_gas_price(), U256::from_u64(10_000_000_000));
+    assert_eq!(tx.unverified_gas_tip_cap(), U256::from_u64(1_000_000_000));
+    assert_eq!(tx.unverified_selector(), [0x35, 0x93, 0x56, 0x4c]);
+    assert_eq!(
+        tx.unverified_calldata_word(1),
+        Some(U256::from_u32(1738475400))
+    );
+
+    let proof = fetcher.transaction_proof(BLOCK, 1).unwrap();
+    assert_eq!(tx.leaf_hash, keccak256(proof.last().unwrap()).0);
+    let raw = verify_proof(hash(TRANSACTIONS_ROOT), &[0x01], &proof)
+        .unwrap()
+        .unwrap();
+    assert_eq!(keccak256(raw).0, tx.hash);
+
+    assert!(matches!(
+        fetcher.transaction(hash(TRANSFER), &[2]),
+        Err(FetchError::Query(QueryError::CalldataOutOfRange {
+            word_index: 2,
+            ..
+        }))
+    ));
+    // the raw transactions of the block are read once for the three calls
+    let raw_tx_requests = requests
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|request| request["method"] == "eth_getRawTransactionByBlockNumberAndIndex")
+        .count();
+    assert_eq!(
+        raw_tx_requests,
+        fetcher.block_transactions(BLOCK).unwrap().len()
+    );
+    // the recorded block 21756847 doesn't hold the transactions of its root
+    assert!(matches!(
+        fetcher.transaction(
+            hash("0x3458f6b96462373fe8c1b3f05f50dedbcf0d3e7b881da8d64e5b1f6a605ac72e"),
+            &[]
+        ),
+        Err(FetchError::TransactionsRootMismatch(21756847))
+    ));
+}
@@ -1,11 +1,33 @@
+use alloy_sol_types::sol;
 use coprocessor_sdk::Hex;
 use coprocessor_sdk::{
     data_types::{address::Address, byte32::Bytes32},
-    input_types::receipt::{LogFieldData, ReceiptData},
+    input_types::{
+        event::EventField,
+        receipt::{LogFieldData, ReceiptData},
+    },
 };
-use crypto_bigint::U256;
+use crypto_bigint::{Zero, U256};
 use serde::{Deserialize, Serialize};
 
+sol! {
+    /// Uniswap V3 pool swap
+    event Swap(address indexed sender, address indexed recipient, int256 amount0, int256 amount1, uint160 sqrtPriceX96, uint128 liquidity, int24 tick);
+
+    /// Output of the program, decoded by the callback after the `PublicValuesStruct`
+    struct TradingVolume {
+        uint256 volume;
+    }
+}
+
+impl From<U256> for TradingVolume {
+    fn from(volume: U256) -> Self {
+        TradingVolume {
+            volume: alloy_primitives::U256::from_be_bytes(volume.to_be_bytes()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradingVolumnReceipts {
     pub receipts: Vec<ReceiptData>,
@@ -15,6 +37,33 @@ pub struct TradingVolumnReceipts {
     pub expect_user_addr: U256,
     pub max_receipts: usize,
 }
+impl TradingVolumnReceipts {
+    /// The summed amount1 of the swaps of the expected user in the USDC pool, computed the
+    /// same way by the prover and by the host building the request.
+    pub fn volume(&self, receipts: &[ReceiptData]) -> U256 {
+        let mut volume = U256::zero();
+        for receipt in receipts {
+            // checks the contract, the event topic and that the fields belong to the same log,
+            // the topic being only proven up to its first 6 bytes under V0
+            let swap = receipt
+                .event::<Swap>(Some(self.expect_usdc_pool))
+                .expect("usdc pool swap event mismatches");
+
+            let receipt_user_addr = swap
+                .value(EventField::Topic(2))
+                .expect("swap recipient missing");
+            if self.expect_user_addr != receipt_user_addr {
+                panic!("user address mismatches");
+            }
+
+            volume += swap
+                .value(EventField::Data(1))
+                .expect("swap amount1 missing");
+        }
+        volume
+    }
+}
+
 // update this to change the workload of trading volumn receipts
 const MAX_RECEIPT: usize = 64;
 
@@ -1,13 +1,13 @@
-use std::{
-    path::PathBuf, process::{Command, Stdio}
-};
+use std::path::PathBuf;
 
 use coprocessor_sdk::sdk::Builder;
 use log::{error, info};
-use trading_volumn_lib::prepare_test_receipts;
+use trading_volumn_lib::{prepare_test_receipts, TradingVolume};
+use vm_coprocessor_server::{CoprocessorClient, GatewayConfig};
 
 // test batchQueryAsync,test submit proof,
-fn main() {
+#[tokio::main]
+async fn main() {
     // Initialize the logger with a default filter level
     env_logger::Builder::from_default_env()
         .filter_level(log::LevelFilter::Info) // Set default log level to Info
@@ -15,57 +15,35 @@ fn main() {
 
     // add data
     let test_receipts = prepare_test_receipts();
-    let mut sdk = Builder::new().with_receipts(test_receipts.receipts).init(64, 0, 0);
-    sdk.chain_id = 1;
+    // the output the prover commits, sent to the gateway with the inputs
+    let volume = test_receipts.volume(&test_receipts.receipts);
+    let mut sdk = Builder::new()
+        .with_chain_id(1)
+        .with_receipts(test_receipts.receipts)
+        .init(test_receipts.max_receipts as u32, 0, 0);
+    sdk.set_output(&TradingVolume::from(volume));
     sdk.save_inputs(PathBuf::from("./example/trading_volumn_prover/inputs/"))
         .unwrap();
 
-    // before you execute this code you must install brevis request bin first
-    // cd network
-    // cargo install --path 。
+    // the gateway is configured by the BREVIS_GATEWAY_* environment variables
+    let config = GatewayConfig::from_env().expect("invalid gateway config");
 
-    info!("Starting brevis-requests process...");
-    let request_brevis_cmd = Command::new("brevis-request")
-        .env("REQUEST_DATA_FILE", "./example/trading_volumn_prover/inputs/request_prove_inputs.json")
-        .stdout(Stdio::piped()) // Capture stdout for logging
-        .stderr(Stdio::piped()) // Capture stderr for logging
-        .spawn();
-
-    match request_brevis_cmd {
-        Ok(child) => {
-            info!("brevis-request process started successfully.");
-
-            // Wait for the process to complete and capture its output
-            let output = child
-                .wait_with_output()
-                .expect("Failed to wait on child process");
-
-            // Log the exit status
-            if output.status.success() {
-                info!("brevis-request process completed successfully.");
-            } else {
-                error!(
-                    "brevis-request process failed with exit code: {:?}",
-                    output.status.code()
-                );
-            }
+    info!("Requesting brevis gateway to prove the inputs...");
+    let mut client = match CoprocessorClient::connect(&config).await {
+        Ok(client) => client,
+        Err(e) => {
+            error!("Failed to connect to brevis gateway: {}", e);
+            return;
+        }
+    };
 
-            // Log the stdout and stderr
-            if !output.stdout.is_empty() {
-                info!(
-                    "brevis-request stdout: {}",
-                    String::from_utf8_lossy(&output.stdout)
-                );
-            }
-            if !output.stderr.is_empty() {
-                error!(
-                    "brevis-request stderr: {}",
-                    String::from_utf8_lossy(&output.stderr)
-                );
-            }
+    match client.submit(&sdk).await {
+        Ok(response) => {
+            info!("batch id: {}", response.batch_id);
+            info!("request ids: {:?}", response.request_ids);
         }
         Err(e) => {
-            error!("Failed to execute brevis-request process: {}", e);
+            error!("brevis gateway request failed: {}", e);
         }
     }
 }